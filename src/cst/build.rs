@@ -0,0 +1,370 @@
+use super::{
+    document::Cst,
+    parser::advance,
+    syntax::{CstNode, Element, NodeKind, Token, TokenKind},
+};
+use crate::{
+    data::{
+        comments::Comments,
+        make::{
+            self,
+            error::{marked, MakeErrorReason},
+            Maker,
+        },
+        mark::Mark,
+        string_style::StringStyle,
+    },
+    parse::{
+        error::{
+            marked::MakeError,
+            Error::{self, NonexistentFile},
+        },
+        parse_classic_string::{classic_string, is_escaped},
+        parse_line_string::line_string,
+        parse_not_escaped_string::not_escaped_string,
+    },
+};
+use std::{
+    borrow::Cow,
+    fs,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
+
+pub(super) type MakeNode<'a> = Box<dyn FnOnce(&mut Maker) -> marked::MakeResult<(), Error> + 'a>;
+
+type BuildResult<T> = Result<T, MakeError>;
+
+type Entries<'a> = Vec<(Cow<'a, str>, MakeNode<'a>)>;
+
+/// Gets the mark of the first token of the node that is not a trivia.
+fn begin_mark(mark: Mark, node: &CstNode) -> (Mark, bool) {
+    let mut mark = mark;
+    for i in node.children() {
+        match i {
+            Element::Token(i) if i.kind().is_trivia() => mark = advance(mark, i.text()),
+            Element::Token(_) => return (mark, true),
+            Element::Node(i) => match begin_mark(mark, i) {
+                (mark, true) => return (mark, true),
+                (end, false) => mark = end,
+            },
+        }
+    }
+    (mark, false)
+}
+
+/// Marks after the node and after its last token that is not a trivia.
+#[derive(Clone, Copy)]
+struct Ends {
+    next: Mark,
+    last: Option<Mark>,
+}
+
+impl Ends {
+    fn token(self, token: &Token) -> Self {
+        let next = advance(self.next, token.text());
+        let last = match token.kind().is_trivia() {
+            true => self.last,
+            false => Some(next),
+        };
+        Self { next, last }
+    }
+
+    fn node(self, inner: Ends) -> Self {
+        Self {
+            next: inner.next,
+            last: inner.last.or(self.last),
+        }
+    }
+}
+
+/// Gets the ends of the node without building it.
+fn skip(mark: Mark, node: &CstNode) -> Ends {
+    let ends = Ends {
+        next: mark,
+        last: None,
+    };
+    node.children().iter().fold(ends, |ends, i| match i {
+        Element::Token(i) => ends.token(i),
+        Element::Node(i) => ends.node(skip(ends.next, i)),
+    })
+}
+
+/// Goes over the children of the node once, passing each to `f` with the mark it begins at.
+///
+/// `f` returns the ends of the child nodes it builds, the other child nodes are walked over.
+fn walk<'a, F>(mark: Mark, node: &'a CstNode, mut f: F) -> BuildResult<Ends>
+where
+    F: FnMut(&'a Element, Mark) -> BuildResult<Option<Ends>>,
+{
+    let mut ends = Ends {
+        next: mark,
+        last: None,
+    };
+    for i in node.children() {
+        ends = match (i, f(i, ends.next)?) {
+            (Element::Token(i), _) => ends.token(i),
+            (Element::Node(_), Some(inner)) => ends.node(inner),
+            (Element::Node(i), None) => ends.node(skip(ends.next, i)),
+        };
+    }
+    Ok(ends)
+}
+
+/// Finds the comment at the end of the first line of the node.
+fn find_line_comment(node: &CstNode) -> ControlFlow<Option<&str>> {
+    for i in node.children() {
+        match i {
+            Element::Token(i) if i.kind() == TokenKind::Newline => return ControlFlow::Break(None),
+            Element::Token(i) if i.kind() == TokenKind::Comment => {
+                return ControlFlow::Break(Some(i.text()))
+            }
+            Element::Token(_) => {}
+            Element::Node(i) => find_line_comment(i)?,
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+fn line_comment(node: &CstNode) -> Option<String> {
+    match find_line_comment(node) {
+        ControlFlow::Break(Some(i)) => Some(i.into()),
+        _ => None,
+    }
+}
+
+fn with_comments<'a>(f: MakeNode<'a>, comments: Comments) -> MakeNode<'a> {
+    match comments.is_empty() {
+        true => f,
+        false => Box::new(make::comments(comments, f)),
+    }
+}
+
+/// Builds the entries of the list or the map, list items have an empty key.
+///
+/// Comment lines before an entry and the comment on its line are attached to its value.
+fn entries<'a>(
+    files: &'a [PathBuf],
+    node: &'a CstNode,
+    mark: Mark,
+) -> BuildResult<(Entries<'a>, Ends)> {
+    let mut result = Vec::new();
+    let mut leading = Vec::new();
+    let mut new_line = false;
+    let ends = walk(mark, node, |entry, mark| {
+        match entry {
+            Element::Token(i) if i.kind() == TokenKind::Newline => new_line = true,
+            Element::Token(i) if i.kind() == TokenKind::Comment && new_line => {
+                leading.push(i.text().into())
+            }
+            Element::Token(_) => {}
+            Element::Node(entry) => {
+                let key = entry.key().unwrap_or_default();
+                let ends = walk(mark, entry, |value, mark| match value {
+                    Element::Node(value) => {
+                        let trailing = line_comment(value).into_iter().collect();
+                        let comments = Comments::new(std::mem::take(&mut leading), trailing);
+                        let (f, ends) = build_node(files, value, mark)?;
+                        result.push((key.clone(), with_comments(f, comments)));
+                        Ok(Some(ends))
+                    }
+                    Element::Token(_) => Ok(None),
+                })?;
+                new_line = false;
+                return Ok(Some(ends));
+            }
+        }
+        Ok(None)
+    })?;
+    Ok((result, ends))
+}
+
+/// Whether the node is a list or a map written with entries, such a node has no line of its own.
+pub(super) fn is_block(node: &CstNode) -> bool {
+    matches!(node.kind(), NodeKind::List | NodeKind::Map) && node.nodes().next().is_some()
+}
+
+/// Builds the value of the document, comments around it are attached to the value.
+fn document<'a>(
+    files: &'a [PathBuf],
+    node: &'a CstNode,
+    mark: Mark,
+) -> BuildResult<(MakeNode<'a>, Ends)> {
+    let mut comments = Comments::default();
+    let mut result = None;
+    let ends = walk(mark, node, |i, mark| match i {
+        Element::Token(i) if i.kind() == TokenKind::Comment => {
+            match result {
+                Some(_) => comments.trailing.push(i.text().into()),
+                None => comments.leading.push(i.text().into()),
+            }
+            Ok(None)
+        }
+        Element::Token(_) => Ok(None),
+        Element::Node(i) => {
+            if !is_block(i) {
+                comments.trailing.extend(line_comment(i));
+            }
+            let (f, ends) = build_node(files, i, mark)?;
+            result = Some(f);
+            Ok(Some(ends))
+        }
+    })?;
+    let result = result.expect("Incorrect syntax tree structure, the node has no value.");
+    Ok((with_comments(result, comments), ends))
+}
+
+/// Builds the value of the list item, map entry, tag or take anchor.
+fn value<'a>(
+    files: &'a [PathBuf],
+    node: &'a CstNode,
+    mark: Mark,
+) -> BuildResult<(MakeNode<'a>, Ends)> {
+    let mut result = None;
+    let ends = walk(mark, node, |i, mark| match i {
+        Element::Node(i) if result.is_none() => {
+            let (f, ends) = build_node(files, i, mark)?;
+            result = Some(f);
+            Ok(Some(ends))
+        }
+        _ => Ok(None),
+    })?;
+    let result = result.expect("Incorrect syntax tree structure, the node has no value.");
+    Ok((result, ends))
+}
+
+/// Whether the paths lead to the same file, paths of files that cannot be read are compared as
+/// written.
+fn same_file(first: &Path, second: &Path) -> bool {
+    match (fs::canonicalize(first), fs::canonicalize(second)) {
+        (Ok(first), Ok(second)) => first == second,
+        _ => first == second,
+    }
+}
+
+fn token_text(node: &CstNode, kind: TokenKind) -> &str {
+    node.token(kind)
+        .map(|i| i.text())
+        .expect("Incorrect syntax tree structure, the token does not exist.")
+}
+
+pub(super) fn string(
+    file_path: &Path,
+    node: &CstNode,
+    mark: Mark,
+) -> BuildResult<(String, StringStyle)> {
+    let text = token_text(node, TokenKind::String);
+    let (result, style) = if text.starts_with('"') {
+        let style = StringStyle::Classic {
+            escaped: is_escaped(text),
+        };
+        (classic_string(file_path, text, node.indent(), mark), style)
+    } else if text.starts_with(">>") {
+        let result = not_escaped_string(file_path, text, node.indent(), mark);
+        (result, StringStyle::NotEscaped)
+    } else {
+        (line_string(file_path, text, mark), StringStyle::Line)
+    };
+    result.map(|(_, string)| (string, style))
+}
+
+/// Creates a function that adds the node to the document.
+///
+/// `files` are the paths of the file the node is in and of the files including it, from the
+/// outermost one to the file of the node.
+pub(super) fn build<'a>(
+    files: &'a [PathBuf],
+    node: &'a CstNode,
+    mark: Mark,
+) -> BuildResult<MakeNode<'a>> {
+    build_node(files, node, mark).map(|(f, _)| f)
+}
+
+/// Creates a function that adds the node to the document, together with the ends of the node.
+fn build_node<'a>(
+    files: &'a [PathBuf],
+    node: &'a CstNode,
+    mark: Mark,
+) -> BuildResult<(MakeNode<'a>, Ends)> {
+    let file_path = files
+        .last()
+        .expect("Incorrect build state, there is no file being built.")
+        .as_path();
+    let (begin, _) = begin_mark(mark, node);
+    let tokens = || skip(mark, node);
+    let (f, ends): (MakeNode<'a>, Ends) = match node.kind() {
+        NodeKind::Document => return document(files, node, mark),
+        NodeKind::ListItem | NodeKind::MapEntry => return value(files, node, mark),
+        NodeKind::Null => (Box::new(make::null(begin, ())), tokens()),
+        NodeKind::Raw => {
+            let raw = token_text(node, TokenKind::Raw);
+            (Box::new(make::raw(begin, (), raw)), tokens())
+        }
+        NodeKind::String => {
+            let (string, style) = string(file_path, node, begin)?;
+            let f = make::string_with_style(begin, (), string, style);
+            (Box::new(f), tokens())
+        }
+        NodeKind::List => {
+            let (items, ends) = entries(files, node, mark)?;
+            let items = items.into_iter().map(|(_, i)| i);
+            (Box::new(make::list(begin, (), items)), ends)
+        }
+        NodeKind::Map => {
+            let (entries, ends) = entries(files, node, mark)?;
+            (Box::new(make::map(begin, (), entries.into_iter())), ends)
+        }
+        NodeKind::Tagged => {
+            let tag = token_text(node, TokenKind::Tag);
+            let (value, ends) = value(files, node, mark)?;
+            (Box::new(make::tag(begin, tag, value)), ends)
+        }
+        NodeKind::TakeAnchor => {
+            let name = &token_text(node, TokenKind::TakeAnchor)[1..];
+            let (value, ends) = value(files, node, mark)?;
+            (Box::new(make::take_anchor(begin, name, value)), ends)
+        }
+        NodeKind::GetAnchor => {
+            let name = &token_text(node, TokenKind::GetAnchor)[1..];
+            (Box::new(make::get_anchor(begin, (), name)), tokens())
+        }
+        NodeKind::File => {
+            let path = Path::new(token_text(node, TokenKind::Path));
+            let path = file_path.parent().unwrap_or(Path::new("")).join(path);
+            let mut anchors = Vec::new();
+            let ends = walk(mark, node, |i, mark| match i {
+                Element::Node(i) => {
+                    let (entries, ends) = entries(files, i, mark)?;
+                    anchors = entries;
+                    Ok(Some(ends))
+                }
+                Element::Token(_) => Ok(None),
+            })?;
+            let f = move |maker: &mut Maker| {
+                // A file including itself, directly or through other files, would be read without
+                // end.
+                if let Some(first) = files.iter().position(|i| same_file(i, &path)) {
+                    let cycle = files[first..].iter().cloned().chain([path]).collect();
+                    let reason = MakeErrorReason::IncludeCycle(cycle);
+                    return Err(MakeError::new_with(begin, file_path, reason));
+                }
+                let source = fs::read_to_string(&path)
+                    .map_err(|_| MakeError::new_with(begin, file_path, NonexistentFile))?;
+                let cst = Cst::parse(path.clone(), &source)?;
+                let files = files
+                    .iter()
+                    .cloned()
+                    .chain([path.clone()])
+                    .collect::<Vec<_>>();
+                let f = build(&files, cst.root(), Mark::default())?;
+                make::file(begin, (), path, anchors.into_iter(), f)(maker)
+            };
+            (Box::new(f), ends)
+        }
+    };
+    let f: MakeNode<'a> = match ends.last {
+        Some(end) => Box::new(make::end(end, f)),
+        None => f,
+    };
+    Ok((f, ends))
+}
@@ -0,0 +1,353 @@
+use super::{
+    build::{build, MakeNode},
+    parser::Parser,
+    syntax::CstNode,
+};
+use crate::{
+    data::{
+        data::Data,
+        make::{self, options::MakeOptions},
+        mark::Mark,
+    },
+    parse::error::marked::MakeError,
+};
+use std::{
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+/// Lossless syntax tree of a document.
+///
+/// Keeps comments, blank lines and the form of every node, so that writing the tree out gives
+/// the source text back, and after edits only the edited nodes change.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Cst {
+    file_path: PathBuf,
+    root: CstNode,
+}
+
+impl Cst {
+    pub fn parse<P: Into<PathBuf>>(file_path: P, source: &str) -> Result<Self, MakeError> {
+        let file_path = file_path.into();
+        let root = Parser::new(file_path.as_path(), source).document()?;
+        Ok(Self { file_path, root })
+    }
+
+    pub fn file_path(&self) -> &Path {
+        self.file_path.as_path()
+    }
+
+    /// Gets the document node, its value is the root node of the document.
+    pub fn root(&self) -> &CstNode {
+        &self.root
+    }
+
+    pub fn root_mut(&mut self) -> &mut CstNode {
+        &mut self.root
+    }
+
+    pub(super) fn build(&self) -> Result<MakeNode<'_>, MakeError> {
+        build(
+            std::slice::from_ref(&self.file_path),
+            &self.root,
+            Mark::default(),
+        )
+    }
+
+    /// Creates the document, files referenced by it are read relative to its path.
+    pub fn to_data(&self) -> Result<Data, MakeError> {
+        self.to_data_with_options(Default::default())
+    }
+
+    pub fn to_data_with_options(&self, options: MakeOptions) -> Result<Data, MakeError> {
+        let anchors = std::iter::empty::<(&str, MakeNode)>();
+        let f = self.build()?;
+        let path = self.file_path.clone();
+        make::make_file_with_options(Mark::default(), (), path, options, anchors, f)
+    }
+}
+
+impl Display for Cst {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::syntax::NodeKind;
+    use super::*;
+    use crate::{
+        data::make::{
+            error::{marked::MakeResult, MakeErrorReason},
+            options::DuplicateKeys,
+            Maker,
+        },
+        data::{comments::Comments, string_style::StringStyle},
+        parse::error::Error::{self, ExpectedListItem, ImpermissibleSpace, NonexistentFile},
+        test_dir::TestDir,
+    };
+
+    type MakeNode<'a> = Box<dyn FnOnce(&mut Maker) -> MakeResult<(), Error> + 'a>;
+
+    const SOURCE: &str = "# Server configuration
+
+name: > main # not a comment
+port: 8080
+motd: &motd \"hello\\n\\tworld\" # greeting
+copy: *motd
+empty: []
+servers:
+	# the first one
+	- = server:
+		host: localhost
+		weight: null # unused
+
+	- null
+";
+
+    #[test]
+    fn test_cst() {
+        let cst = Cst::parse("test.ieml", SOURCE).unwrap();
+        assert_eq!(cst.to_string(), SOURCE);
+
+        let root = cst.root();
+        assert_eq!(root.get("port").unwrap().kind(), NodeKind::Raw);
+        assert_eq!(root.get("copy").unwrap().kind(), NodeKind::GetAnchor);
+        let server = root.get("servers").unwrap().get_index(0).unwrap();
+        assert_eq!(server.kind(), NodeKind::Tagged);
+        assert_eq!(server.get("host").unwrap().to_string(), " localhost");
+        assert_eq!(server.get("weight").unwrap().to_string(), " null # unused");
+
+        let mark = Mark::default();
+        let server: Vec<(&str, MakeNode)> = vec![
+            ("host", Box::new(make::raw(mark, (), "localhost"))),
+            ("weight", Box::new(make::null(mark, ()))),
+        ];
+        let servers: Vec<MakeNode> = vec![
+            Box::new(make::tag(
+                mark,
+                "server",
+                make::map(mark, (), server.into_iter()),
+            )),
+            Box::new(make::null(mark, ())),
+        ];
+        let entries: Vec<(&str, MakeNode)> = vec![
+            (
+                "name",
+                Box::new(make::string(mark, (), "main # not a comment")),
+            ),
+            ("port", Box::new(make::raw(mark, (), "8080"))),
+            (
+                "motd",
+                Box::new(make::take_anchor(
+                    mark,
+                    "motd",
+                    make::string(mark, (), "hello\n\tworld"),
+                )),
+            ),
+            ("copy", Box::new(make::get_anchor(mark, (), "motd"))),
+            (
+                "empty",
+                Box::new(make::list(mark, (), Vec::<MakeNode>::new().into_iter())),
+            ),
+            (
+                "servers",
+                Box::new(make::list(mark, (), servers.into_iter())),
+            ),
+        ];
+        let anchors = std::iter::empty::<(&str, MakeNode)>();
+        let f = make::map(mark, (), entries.into_iter());
+        let expected = make::make_file(mark, (), "test.ieml".into(), anchors, f).unwrap();
+        let data = cst.to_data().unwrap();
+        assert_eq!(data.view(), expected.view());
+
+        let map = data.view().map().unwrap();
+        let name = map.get("name").unwrap().string().unwrap();
+        assert_eq!(name.style(), StringStyle::Line);
+        let motd = map.get("motd").unwrap().string().unwrap();
+        assert_eq!(motd.style(), StringStyle::Classic { escaped: true });
+        let emitted = data.to_string();
+        assert!(emitted.contains("\nmotd: &motd \"hello\\n\\tworld\" # greeting\n"));
+
+        let view = data.view().map().unwrap().get("servers").unwrap();
+        let server = view.list().unwrap().get(0).unwrap();
+        assert_eq!(server.mark(), Mark::new(9, 3));
+        let host = server
+            .tagged()
+            .unwrap()
+            .view()
+            .map()
+            .unwrap()
+            .get("host")
+            .unwrap();
+        assert_eq!(host.mark(), Mark::new(10, 8));
+    }
+
+    #[test]
+    fn test_comments() {
+        let data = Cst::parse("test.ieml", SOURCE).unwrap().to_data().unwrap();
+        let map = data.view().map().unwrap();
+        let root = data.view().clear_step_file().unwrap();
+        let comments = Comments::new(vec!["# Server configuration".into()], vec![]);
+        assert_eq!(root.comments(), Some(&comments));
+        assert_eq!(map.get("port").unwrap().comments(), None);
+        let motd = map.get("motd").unwrap().comments().unwrap();
+        assert_eq!(motd, &Comments::new(vec![], vec!["# greeting".into()]));
+
+        let servers = map.get("servers").unwrap();
+        let server = servers.list().unwrap().get(0).unwrap();
+        let comments = Comments::new(vec!["# the first one".into()], vec![]);
+        assert_eq!(server.comments(), Some(&comments));
+        let weight = server
+            .tagged()
+            .unwrap()
+            .view()
+            .map()
+            .unwrap()
+            .get("weight")
+            .unwrap();
+        let comments = Comments::new(vec![], vec!["# unused".into()]);
+        assert_eq!(weight.comments(), Some(&comments));
+
+        let expected = "# Server configuration
+name: > main # not a comment
+port: 8080
+motd: &motd \"hello\\n\\tworld\" # greeting
+copy: *motd
+empty: []
+servers:
+	# the first one
+	- = server:
+		host: localhost
+		weight: null # unused
+	- null
+";
+        assert_eq!(data.to_string(), expected);
+
+        let source = "# head\nlist: # items\n\t- a\n# tail\n";
+        let data = Cst::parse("test.ieml", source).unwrap().to_data().unwrap();
+        let list = data.view().map().unwrap().get("list").unwrap();
+        let comments = Comments::new(vec![], vec!["# items".into()]);
+        assert_eq!(list.comments(), Some(&comments));
+        assert_eq!(data.to_string(), source);
+    }
+
+    #[test]
+    fn test_cst_file() {
+        let dir = TestDir::new("cst_file");
+        std::fs::write(
+            dir.join("main.ieml"),
+            "include: < include.ieml\n\tname: hello\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("include.ieml"), "value: *name\n").unwrap();
+
+        let path = dir.join("main.ieml");
+        let source = std::fs::read_to_string(&path).unwrap();
+        let data = Cst::parse(path, &source).unwrap().to_data().unwrap();
+        let include = data.view().map().unwrap().get("include").unwrap();
+        let file = include.file().unwrap();
+        assert_eq!(file.path(), dir.join("include.ieml"));
+        let value = file.view().map().unwrap().get("value").unwrap();
+        assert_eq!(
+            value.clear_step_get_anchor().unwrap().raw().unwrap().raw(),
+            "hello"
+        );
+
+        let data = Cst::parse(dir.join("main.ieml"), "< missing.ieml\n").unwrap();
+        let error = MakeError::new_with(Mark::default(), dir.join("main.ieml"), NonexistentFile);
+        assert_eq!(data.to_data(), Err(error));
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let dir = TestDir::new("cst_include_cycle");
+        std::fs::write(dir.join("self.ieml"), "a: < self.ieml\n").unwrap();
+        let path = dir.join("self.ieml");
+        let data = Cst::parse(&path, "a: < self.ieml\n").unwrap();
+        let cycle = vec![path.clone(), path.clone()];
+        let error =
+            MakeError::new_with(Mark::new(0, 3), path, MakeErrorReason::IncludeCycle(cycle));
+        assert_eq!(data.to_data(), Err(error));
+
+        std::fs::write(dir.join("a.ieml"), "b: < b.ieml\n").unwrap();
+        std::fs::write(dir.join("b.ieml"), "- < a.ieml\n").unwrap();
+        let (a, b) = (dir.join("a.ieml"), dir.join("b.ieml"));
+        let data = Cst::parse(&a, "b: < b.ieml\n").unwrap();
+        let cycle = vec![a.clone(), b.clone(), a.clone()];
+        let error = MakeError::new_with(Mark::new(0, 2), b, MakeErrorReason::IncludeCycle(cycle));
+        assert_eq!(data.to_data(), Err(error));
+    }
+
+    #[test]
+    fn test_cst_error() {
+        let source = "list:\n\t- a\n\tb: c\n";
+        let error = MakeError::new_with(Mark::new(2, 1), "test.ieml", ExpectedListItem);
+        assert_eq!(Cst::parse("test.ieml", source), Err(error));
+
+        let source = "list:\n\t - a\n";
+        let error = MakeError::new_with(Mark::new(1, 1), "test.ieml", ImpermissibleSpace);
+        assert_eq!(Cst::parse("test.ieml", source), Err(error));
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let source = "port: &p 80\nhost: a\nport: 8080\ncopy: *p\n";
+        let cst = Cst::parse("test.ieml", source).unwrap();
+        let reason = MakeErrorReason::DuplicateKey {
+            key: "port".into(),
+            first: Mark::new(0, 6),
+            second: Mark::new(2, 6),
+        };
+        let error = MakeError::new_with(Mark::new(2, 6), "test.ieml", reason);
+        assert_eq!(cst.to_data(), Err(error));
+
+        let options = MakeOptions::new(DuplicateKeys::FirstWins);
+        let data = cst.to_data_with_options(options).unwrap();
+        assert_eq!(data.to_string(), "port: &p 80\nhost: a\ncopy: *p\n");
+        assert_eq!(data.validate(), Ok(()));
+
+        let options = MakeOptions::new(DuplicateKeys::LastWins);
+        let error = cst.to_data_with_options(options).unwrap_err();
+        let reason = MakeErrorReason::AnchorDoesntExist("p".into());
+        assert_eq!(error.data.reason, reason);
+
+        let source = "port: 80\nhost: a\nport: 8080\n";
+        let data = Cst::parse("test.ieml", source)
+            .unwrap()
+            .to_data_with_options(options)
+            .unwrap();
+        assert_eq!(data.to_string(), "port: 8080\nhost: a\n");
+        assert_eq!(data.validate(), Ok(()));
+
+        let source = "host: a\n# first\nport: 80\n# second\nport: 8080\n";
+        let cst = Cst::parse("test.ieml", source).unwrap();
+        for (duplicate_keys, kept) in [
+            (DuplicateKeys::FirstWins, "host: a\n# first\nport: 80\n"),
+            (DuplicateKeys::LastWins, "host: a\n# second\nport: 8080\n"),
+        ] {
+            let options = MakeOptions::new(duplicate_keys);
+            let data = cst.to_data_with_options(options).unwrap();
+            assert_eq!(data.to_string(), kept);
+            assert_eq!(data.comments.len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_anchor_cycle() {
+        let source = "a: &a\n\t- *b\nb: &b\n\t- *a\n";
+        let reason = MakeErrorReason::AnchorCycle(vec!["b".into(), "a".into(), "b".into()]);
+        let error = MakeError::new_with(Mark::new(3, 3), "test.ieml", reason);
+        let data = Cst::parse("test.ieml", source).unwrap().to_data();
+        assert_eq!(data, Err(error));
+
+        let source = "a: &a *a\n";
+        let data = Cst::parse("test.ieml", source).unwrap().to_data();
+        let reason = MakeErrorReason::AnchorCycle(vec!["a".into(), "a".into()]);
+        assert_eq!(data.unwrap_err().data.reason, reason);
+
+        let source = "a: &a\n\t- *b\nb: &b\n\t- *b2\nb2: &b2 1\n";
+        assert!(Cst::parse("test.ieml", source).unwrap().to_data().is_ok());
+    }
+}
@@ -0,0 +1,245 @@
+use super::{
+    parser::{is_list_item, key_len, parse_inline},
+    syntax::{CstNode, Element, NodeKind, Token, TokenKind},
+};
+use crate::{
+    data::mark::Mark,
+    parse::error::{marked::MakeError, Error::ExpectedMapKey},
+};
+use std::path::Path;
+
+fn is_trivia(element: &Element, kind: Option<TokenKind>) -> bool {
+    match element {
+        Element::Token(i) => i.kind().is_trivia() && (kind.is_none() || kind == Some(i.kind())),
+        Element::Node(_) => false,
+    }
+}
+
+fn space() -> Element {
+    Element::Token(Token::new(TokenKind::Space, " "))
+}
+
+impl CstNode {
+    fn leading_len(&self) -> usize {
+        self.children
+            .iter()
+            .take_while(|i| is_trivia(i, None))
+            .count()
+    }
+
+    fn trailing_len(&self) -> usize {
+        let iter = self.children.iter().rev();
+        iter.take_while(|i| {
+            is_trivia(i, Some(TokenKind::Space)) || is_trivia(i, Some(TokenKind::Comment))
+        })
+        .count()
+    }
+
+    fn is_single_line(&self) -> bool {
+        !self.children[self.leading_len()..]
+            .iter()
+            .any(|i| i.to_string().contains('\n'))
+    }
+
+    /// Replaces the node with the node written in the source on one line.
+    ///
+    /// Multiline strings in the source have to be indented as the contents of this node.
+    /// The space before the node and the comment after it are kept. Errors refer to `file_path`,
+    /// the path of the document the node is in.
+    pub fn replace(&mut self, file_path: &Path, source: &str) -> Result<(), MakeError> {
+        if let NodeKind::Document | NodeKind::ListItem | NodeKind::MapEntry = self.kind {
+            return self
+                .value_mut()
+                .expect("Incorrect syntax tree structure, the node has no value.")
+                .replace(file_path, source);
+        }
+        let mut node = parse_inline(file_path, source, self.indent)?;
+        let leading = &self.children[..self.leading_len()];
+        let spaces = leading.iter().all(|i| is_trivia(i, Some(TokenKind::Space)));
+        let mut children = match (spaces, leading.is_empty() && self.indent != 0) {
+            (true, false) => leading.to_vec(),
+            _ => vec![space()],
+        };
+        children.append(&mut node.children);
+        if self.is_single_line() && node.trailing_len() == 0 && !source.contains('\n') {
+            let trailing = &self.children[self.children.len() - self.trailing_len()..];
+            children.extend_from_slice(trailing);
+        }
+        node.children = children;
+        *self = node;
+        Ok(())
+    }
+
+    /// Sets the value of the map entry, adding the entry to the end of the map if there is none.
+    ///
+    /// The value is written in the source on one line. Errors refer to `file_path`, the path of
+    /// the document the map is in.
+    pub fn insert(&mut self, file_path: &Path, key: &str, source: &str) -> Result<(), MakeError> {
+        if let Some(value) = self.get_mut(key) {
+            return value.replace(file_path, source);
+        }
+        let map = self.inner_mut();
+        let line = format!("{}:", key);
+        if map.kind != NodeKind::Map || is_list_item(&line) || key_len(&line) != Some(key.len()) {
+            return Err(MakeError::new_with(
+                Mark::default(),
+                file_path,
+                ExpectedMapKey,
+            ));
+        }
+        let mut value = parse_inline(file_path, source, map.indent + 1)?;
+        value.children.insert(0, space());
+        let entry = CstNode::new(
+            NodeKind::MapEntry,
+            map.indent,
+            vec![
+                Element::Token(Token::new(TokenKind::Key, key)),
+                Element::Token(Token::new(TokenKind::Colon, ":")),
+                Element::Node(value),
+            ],
+        );
+        if map.token(TokenKind::EmptyMap).is_some() {
+            map.children.clear();
+        }
+        if map.indent != 0 || map.nodes().next().is_some() {
+            map.children
+                .push(Element::Token(Token::new(TokenKind::Newline, "\n")));
+            let indent = "\t".repeat(map.indent);
+            map.children
+                .push(Element::Token(Token::new(TokenKind::Indent, indent)));
+        }
+        map.children.push(Element::Node(entry));
+        Ok(())
+    }
+
+    /// Removes the entry of the map together with the lines before it, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<CstNode> {
+        let map = self.inner_mut();
+        if map.kind != NodeKind::Map {
+            return None;
+        }
+        let index = map.children.iter().position(|i| match i {
+            Element::Node(i) => i.key().as_deref() == Some(key),
+            Element::Token(_) => false,
+        })?;
+        let run = map.children[..index]
+            .iter()
+            .rev()
+            .take_while(|i| is_trivia(i, None))
+            .count();
+        let newline =
+            (index - run..index).find(|i| is_trivia(&map.children[*i], Some(TokenKind::Newline)));
+        let removed = match newline {
+            Some(begin) => map.children.drain(begin..=index).next_back(),
+            None => {
+                let end = match map.children.get(index + 1) {
+                    Some(i) if is_trivia(i, Some(TokenKind::Newline)) => index + 1,
+                    _ => index,
+                };
+                map.children.drain(index..=end).next()
+            }
+        };
+        if map.nodes().next().is_none() {
+            map.replace(Path::new(""), "{}")
+                .expect("Internal error, an empty map cannot fail to be parsed.");
+        }
+        match removed {
+            Some(Element::Node(entry)) => entry.children.into_iter().find_map(|i| match i {
+                Element::Node(i) => Some(i),
+                Element::Token(_) => None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::document::Cst;
+    use super::*;
+
+    const SOURCE: &str = "# Package
+version: 1.2.3 # bumped by the release script
+features:
+	# enabled by default
+	- default
+dependencies:
+	# parsing
+	nom: 7.1.3
+
+	num: 0.4.0
+";
+
+    #[test]
+    fn test_replace() {
+        let path = Path::new("test.ieml");
+        let mut cst = Cst::parse("test.ieml", SOURCE).unwrap();
+        let version = cst.root_mut().get_mut("version").unwrap();
+        version
+            .replace(path, "1.2.4 # bumped by the release script")
+            .unwrap();
+        let features = cst.root_mut().get_mut("features").unwrap();
+        features
+            .get_index_mut(0)
+            .unwrap()
+            .replace(path, "> all")
+            .unwrap();
+        let expected = SOURCE
+            .replace("1.2.3", "1.2.4")
+            .replace("- default", "- > all");
+        assert_eq!(cst.to_string(), expected);
+
+        let mut cst = Cst::parse("test.ieml", "key: null # nothing\n").unwrap();
+        cst.root_mut()
+            .get_mut("key")
+            .unwrap()
+            .replace(path, "\"value\"")
+            .unwrap();
+        assert_eq!(cst.to_string(), "key: \"value\" # nothing\n");
+
+        let mut cst = Cst::parse("test.ieml", SOURCE).unwrap();
+        cst.root_mut()
+            .get_mut("features")
+            .unwrap()
+            .replace(path, "[]")
+            .unwrap();
+        assert!(cst.to_string().contains("\nfeatures: []\ndependencies:\n"));
+        let error = cst.root_mut().replace(path, "> hello\nworld").unwrap_err();
+        assert_eq!(error.data.file_path, path);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let path = Path::new("test.ieml");
+        let mut cst = Cst::parse("test.ieml", SOURCE).unwrap();
+        let dependencies = cst.root_mut().get_mut("dependencies").unwrap();
+        dependencies.insert(path, "indexmap", "2.0.0").unwrap();
+        dependencies.insert(path, "num", "0.4.1").unwrap();
+        let error = MakeError::new_with(Mark::default(), path, ExpectedMapKey);
+        assert_eq!(dependencies.insert(path, "- item", "1"), Err(error));
+        assert!(dependencies.insert(path, "# comment", "1").is_err());
+        let expected = SOURCE.replace("num: 0.4.0\n", "num: 0.4.1\n\tindexmap: 2.0.0\n");
+        assert_eq!(cst.to_string(), expected);
+
+        let dependencies = cst.root_mut().get_mut("dependencies").unwrap();
+        let nom = dependencies.remove("nom").unwrap();
+        assert_eq!(nom.to_string(), " 7.1.3");
+        assert!(dependencies.remove("nom").is_none());
+        let expected = expected.replace("\t# parsing\n\tnom: 7.1.3\n", "");
+        assert_eq!(cst.to_string(), expected);
+
+        let dependencies = cst.root_mut().get_mut("dependencies").unwrap();
+        dependencies.remove("num").unwrap();
+        dependencies.remove("indexmap").unwrap();
+        let expected = SOURCE.split("dependencies:").next().unwrap().to_string();
+        assert_eq!(cst.to_string(), expected + "dependencies: {}\n");
+
+        let dependencies = cst.root_mut().get_mut("dependencies").unwrap();
+        dependencies.insert(path, "nom", "7.1.3").unwrap();
+        assert!(cst.to_string().ends_with("\ndependencies:\n\tnom: 7.1.3\n"));
+
+        cst.root_mut().remove("version").unwrap();
+        assert!(cst.to_string().starts_with("# Package\nfeatures:\n"));
+        cst.to_data().unwrap();
+    }
+}
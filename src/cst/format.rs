@@ -0,0 +1,406 @@
+use super::{
+    build::{is_block, string},
+    document::Cst,
+    syntax::{CstNode, Element, NodeKind, Token, TokenKind},
+};
+use crate::{
+    data::{mark::Mark, string_style::StringStyle},
+    emit::string::{fitting_style, write_string},
+    parse::error::marked::MakeError,
+};
+use std::path::PathBuf;
+
+/// How strings are written by the formatter.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum StringFormat {
+    /// Keeps the form each string was written in.
+    #[default]
+    Keep,
+    /// Writes each string in the form preferred for its contents, see [`StringStyle::preferred`].
+    Preferred,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct FormatOptions {
+    /// Whether the entries of maps are sorted by key.
+    pub sort_keys: bool,
+    pub strings: StringFormat,
+}
+
+impl FormatOptions {
+    pub fn new(sort_keys: bool, strings: StringFormat) -> Self {
+        Self { sort_keys, strings }
+    }
+}
+
+/// Comment of each line of the trivia, `None` for a blank line.
+///
+/// The first line is the rest of the line the trivia begins on.
+fn lines<'a>(tokens: &[&'a Token]) -> Vec<Option<&'a str>> {
+    let mut result = vec![None];
+    for i in tokens {
+        match i.kind() {
+            TokenKind::Newline => result.push(None),
+            TokenKind::Comment => *result.last_mut().unwrap() = Some(i.text()),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Removes repeated blank lines, and the blank lines at the beginning unless `keep_leading`.
+fn collapse<'a>(lines: &[Option<&'a str>], keep_leading: bool) -> Vec<Option<&'a str>> {
+    let mut result = Vec::new();
+    let mut blank = !keep_leading;
+    for i in lines {
+        if i.is_some() || !blank {
+            result.push(*i);
+        }
+        blank = i.is_none();
+    }
+    result
+}
+
+type Entry<'a> = (Vec<Option<&'a str>>, &'a CstNode);
+
+/// Splits the list or the map written with entries into the comment on its first line and the
+/// entries with the lines before each of them.
+fn entries(node: &CstNode) -> (Option<&str>, Vec<Entry<'_>>) {
+    let mut header = None;
+    let mut result = Vec::new();
+    let mut run = Vec::new();
+    for i in node.children() {
+        match i {
+            Element::Token(i) => run.push(i),
+            Element::Node(i) => {
+                let lines = lines(&run);
+                if result.is_empty() {
+                    header = lines[0];
+                }
+                let end = lines.len().max(2) - 1;
+                result.push((lines[1..end].to_vec(), i));
+                run.clear();
+            }
+        }
+    }
+    (header, result)
+}
+
+/// Gets the comment at the end of the line of the node written on one line.
+fn comment(node: &CstNode) -> Option<&str> {
+    node.token(TokenKind::Comment).map(Token::text)
+}
+
+/// Gets the comment after `>>` of the string written with it.
+fn header_comment(node: &CstNode) -> Option<&str> {
+    let token = node.token(TokenKind::String)?.text();
+    let header = token.split('\n').next()?.trim_start_matches('>').trim();
+    header.starts_with('#').then_some(header)
+}
+
+struct Formatter<'a> {
+    cst: &'a Cst,
+    options: FormatOptions,
+    output: String,
+}
+
+impl Formatter<'_> {
+    fn push_comment(&mut self, comment: Option<&str>) {
+        if let Some(comment) = comment {
+            self.output.push(' ');
+            self.output.push_str(comment);
+        }
+    }
+
+    /// Writes the lines, each comment with `indent`, a blank line without any indentation.
+    fn push_lines(&mut self, lines: &[Option<&str>], indent: usize) {
+        for i in lines {
+            match i {
+                Some(comment) => {
+                    self.new_line(indent);
+                    self.output.push_str(comment);
+                }
+                None => self.output.push('\n'),
+            }
+        }
+    }
+
+    fn new_line(&mut self, indent: usize) {
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+        self.output.push_str(&"\t".repeat(indent));
+    }
+
+    fn document(&mut self, node: &CstNode) {
+        let mut leading = Vec::new();
+        let mut trailing = Vec::new();
+        let mut value = None;
+        for i in node.children() {
+            match (i, value) {
+                (Element::Token(i), None) => leading.push(i),
+                (Element::Token(i), Some(_)) => trailing.push(i),
+                (Element::Node(i), _) => value = Some(i),
+            }
+        }
+        let value = value.expect("Incorrect syntax tree structure, the node has no value.");
+
+        let leading = lines(&leading);
+        let lines_end = leading.len() - 1;
+        let mut leading = collapse(&leading[..lines_end], false);
+        if leading.iter().all(Option::is_none) {
+            leading.clear();
+        }
+        self.push_lines(&leading, 0);
+
+        match is_block(value) {
+            true => self.block(value, 0),
+            false => {
+                self.new_line(0);
+                self.inline(value, 0);
+            }
+        }
+
+        let mut trailing = collapse(&lines(&trailing)[1..], true);
+        while trailing.last().is_some_and(Option::is_none) {
+            trailing.pop();
+        }
+        self.push_lines(&trailing, 0);
+        self.output.push('\n');
+    }
+
+    /// Writes the entries of the list or the map, preceded by the comment on its first line.
+    fn block(&mut self, node: &CstNode, indent: usize) {
+        let (header, mut entries) = entries(node);
+        self.push_comment(header);
+        if self.options.sort_keys && node.kind() == NodeKind::Map {
+            entries.sort_by_key(|(_, i)| i.key());
+        }
+        for (i, (lines, entry)) in entries.into_iter().enumerate() {
+            self.push_lines(&collapse(&lines, i != 0), indent);
+            self.new_line(indent);
+            match entry.token(TokenKind::Key) {
+                Some(key) => {
+                    self.output.push_str(key.text());
+                    self.output.push(':');
+                }
+                None => self.output.push('-'),
+            }
+            let value = entry
+                .value()
+                .expect("Incorrect syntax tree structure, the node has no value.");
+            self.value(value, indent + 1);
+        }
+    }
+
+    /// Writes the node after a prefix such as `key:`, `-` or a tag.
+    fn value(&mut self, node: &CstNode, indent: usize) {
+        match is_block(node) {
+            true => self.block(node, indent),
+            false => {
+                self.output.push(' ');
+                self.inline(node, indent);
+            }
+        }
+    }
+
+    fn inline(&mut self, node: &CstNode, indent: usize) {
+        let token = |kind| {
+            node.token(kind)
+                .map(Token::text)
+                .expect("Incorrect syntax tree structure, the token does not exist.")
+        };
+        match node.kind() {
+            NodeKind::Null => self.output.push_str("null"),
+            NodeKind::Raw => self.output.push_str(token(TokenKind::Raw)),
+            NodeKind::String => return self.string(node, indent),
+            NodeKind::List => self.output.push_str("[]"),
+            NodeKind::Map => self.output.push_str("{}"),
+            NodeKind::GetAnchor => self.output.push_str(token(TokenKind::GetAnchor)),
+            NodeKind::TakeAnchor | NodeKind::Tagged => {
+                match node.kind() {
+                    NodeKind::TakeAnchor => self.output.push_str(token(TokenKind::TakeAnchor)),
+                    _ => {
+                        self.output.push_str("= ");
+                        self.output.push_str(token(TokenKind::Tag));
+                        self.output.push(':');
+                    }
+                }
+                let value = node
+                    .value()
+                    .expect("Incorrect syntax tree structure, the node has no value.");
+                return self.value(value, indent);
+            }
+            NodeKind::File => {
+                self.output.push_str("< ");
+                self.output.push_str(token(TokenKind::Path));
+                self.push_comment(comment(node));
+                if let Some(anchors) = node.nodes().next() {
+                    self.block(anchors, indent);
+                }
+                return;
+            }
+            NodeKind::Document | NodeKind::ListItem | NodeKind::MapEntry => {
+                unreachable!("Incorrect syntax tree structure, the node is not a value.")
+            }
+        }
+        self.push_comment(comment(node));
+    }
+
+    fn string(&mut self, node: &CstNode, indent: usize) {
+        let (string, original) = string(self.cst.file_path(), node, Mark::default())
+            .expect("Incorrect syntax tree structure, the string is not valid.");
+        let style = match self.options.strings {
+            StringFormat::Keep => fitting_style(&string, original),
+            StringFormat::Preferred => StringStyle::preferred(&string),
+        };
+        let comment = match original {
+            StringStyle::NotEscaped => header_comment(node),
+            _ => comment(node),
+        };
+        let style = match (style, comment) {
+            (StringStyle::Line, Some(_)) => StringStyle::Classic { escaped: true },
+            _ => style,
+        };
+        write_string(&mut self.output, &string, style, indent, comment);
+    }
+}
+
+impl Cst {
+    /// Writes the document in the canonical form.
+    ///
+    /// Indentation and spacing are normalized, repeated blank lines are merged, comments are kept
+    /// with the nodes they belong to. Formatting the result again gives the same text.
+    pub fn format(&self, options: FormatOptions) -> String {
+        let mut formatter = Formatter {
+            cst: self,
+            options,
+            output: String::new(),
+        };
+        formatter.document(self.root());
+        formatter.output
+    }
+}
+
+/// Parses the document and writes it in the canonical form.
+pub fn format<P: Into<PathBuf>>(
+    file_path: P,
+    source: &str,
+    options: FormatOptions,
+) -> Result<String, MakeError> {
+    Ok(Cst::parse(file_path, source)?.format(options))
+}
+
+/// Checks whether the document is already written in the canonical form.
+pub fn check<P: Into<PathBuf>>(
+    file_path: P,
+    source: &str,
+    options: FormatOptions,
+) -> Result<bool, MakeError> {
+    Ok(format(file_path, source, options)? == source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data::value::Value, parse::error::Error::ExpectedMapKey};
+    use std::convert::Infallible;
+
+    const SOURCE: &str = "
+
+# Server configuration
+
+
+name: \"main\"   # primary
+ports:    # exposed
+\t- 80\t
+\t# secure
+\t- 443
+empty: []  \t
+motd: >>   # greeting
+\thello
+\tworld
+server: &server = server:  # tagged
+\thost: localhost
+\tweight: null    # unused
+include: < include.ieml   # shared
+\tserver: *server  
+
+# end
+
+";
+
+    const FORMATTED: &str = "# Server configuration
+
+name: \"main\" # primary
+ports: # exposed
+\t- 80\t
+\t# secure
+\t- 443
+empty: []
+motd: >> # greeting
+\thello
+\tworld
+server: &server = server: # tagged
+\thost: localhost
+\tweight: null # unused
+include: < include.ieml # shared
+\tserver: *server
+
+# end
+";
+
+    #[test]
+    fn test_format() {
+        let formatted = format("test.ieml", SOURCE, Default::default()).unwrap();
+        assert_eq!(formatted, FORMATTED);
+        let again = format("test.ieml", &formatted, Default::default()).unwrap();
+        assert_eq!(again, formatted);
+
+        let source = "a: b\n\n\n\nc: d\n";
+        let formatted = format("test.ieml", source, Default::default()).unwrap();
+        assert_eq!(formatted, "a: b\n\nc: d\n");
+
+        let formatted = format("test.ieml", "null # nothing", Default::default()).unwrap();
+        assert_eq!(formatted, "null # nothing\n");
+    }
+
+    #[test]
+    fn test_format_sort_keys() {
+        let options = FormatOptions::new(true, StringFormat::Keep);
+        let source = "b: 2\n# about a\na:\n\td: 4\n\n\tc: 3\n";
+        let expected = "# about a\na:\n\tc: 3\n\td: 4\nb: 2\n";
+        let formatted = format("test.ieml", source, options).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(check("test.ieml", &formatted, options), Ok(true));
+    }
+
+    #[test]
+    fn test_format_strings() {
+        let options = FormatOptions::new(false, StringFormat::Preferred);
+        let source =
+            "a: \"line\"\nb: \"first\\nsecond\"\nc: > trailing \nd: \"\"\ne: \"x\" # kept\n";
+        let expected =
+            "a: > line\nb: >>\n\tfirst\n\tsecond\nc: \"trailing \"\nd: \"\"\ne: \"x\" # kept\n";
+        let formatted = format("test.ieml", source, options).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(check("test.ieml", &formatted, options), Ok(true));
+
+        let before = Cst::parse("test.ieml", source).unwrap();
+        let after = Cst::parse("test.ieml", &formatted).unwrap();
+        let value = |i: &Cst| {
+            let data = i.to_data().unwrap();
+            data.view().decode::<Infallible, Value>().unwrap()
+        };
+        assert_eq!(value(&before), value(&after));
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(check("test.ieml", FORMATTED, Default::default()), Ok(true));
+        assert_eq!(check("test.ieml", SOURCE, Default::default()), Ok(false));
+        let error = MakeError::new_with(Mark::new(1, 0), "test.ieml", ExpectedMapKey);
+        let result = check("test.ieml", "a: b\n- c\n", Default::default());
+        assert_eq!(result, Err(error));
+    }
+}
@@ -0,0 +1,6 @@
+mod build;
+pub mod document;
+pub mod edit;
+pub mod format;
+pub(crate) mod parser;
+pub mod syntax;
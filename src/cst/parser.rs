@@ -0,0 +1,404 @@
+use super::syntax::{CstNode, Element, NodeKind, Token, TokenKind};
+use crate::{
+    data::{mark::Mark, node_path::quoted_key},
+    parse::{
+        error::{
+            marked::MakeError,
+            Error::{
+                self, ExpectedBlankLine, ExpectedListItem, ExpectedMapKey, FailedDetermineType,
+                ImpermissibleSpace, ImpermissibleTab, IncompleteDocument, IncompleteString,
+            },
+        },
+        parse_classic_string::classic_string,
+        parse_not_escaped_string::not_escaped_string,
+        parse_raw::raw,
+        utils::combinator::match_blank_line,
+    },
+};
+use std::{borrow::Cow, path::Path};
+
+type ParseResult<T> = Result<T, MakeError>;
+
+pub(super) fn advance(mark: Mark, text: &str) -> Mark {
+    match text.rfind('\n') {
+        Some(i) => Mark::new(mark.line + text.matches('\n').count(), text.len() - i - 1),
+        None => mark + Mark::new(0, text.len()),
+    }
+}
+
+fn is_blank(line: &str) -> bool {
+    match_blank_line(line).0.is_empty()
+}
+
+pub(super) fn is_list_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+/// Gets the length of the key if the line content is a map entry.
+///
+/// A key that is not plain is written in quotes with escapes, like a key of a `NodePath`.
+pub(super) fn key_len(content: &str) -> Option<usize> {
+    if content.starts_with('"') {
+        let (_, len) = quoted_key(content)?;
+        let rest = content[len..].strip_prefix(':')?;
+        return (rest.is_empty() || rest.starts_with([' ', '\n'])).then_some(len);
+    }
+    if content.starts_with(['>', '<', '=', '&', '*', '#', ' ', '\t', '[', '{']) {
+        return None;
+    }
+    let (index, _) = content.match_indices(':').find(|(i, _)| {
+        let rest = &content[i + 1..];
+        rest.is_empty() || rest.starts_with([' ', '\n'])
+    })?;
+    let key = &content[..index];
+    (!key.is_empty() && !key.contains(['"', '<', '>', '\n'])).then_some(index)
+}
+
+/// Gets the text of the key as it is written in a map entry, in quotes if it is not plain.
+pub(crate) fn key_text(key: &str) -> Cow<'_, str> {
+    let line = format!("{}:", key);
+    match !key.starts_with('"') && !is_list_item(&line) && key_len(&line) == Some(key.len()) {
+        true => Cow::Borrowed(key),
+        false => Cow::Owned(format!("{:?}", key)),
+    }
+}
+
+/// Whether the text is read back as the same raw value when written after `key:` or `-`.
+pub(crate) fn is_raw(raw: &str) -> bool {
+    let node = match is_blank(raw) || is_list_item(raw) || key_len(raw).is_some() {
+        true => return false,
+        false => parse_inline(Path::new(""), raw, 0),
+    };
+    matches!(node, Ok(node) if node.kind == NodeKind::Raw)
+}
+
+fn tabs(line: &str) -> usize {
+    line.len() - line.trim_start_matches('\t').len()
+}
+
+pub(super) struct Parser<'input, 'path> {
+    file_path: &'path Path,
+    input: &'input str,
+    mark: Mark,
+}
+
+impl<'input, 'path> Parser<'input, 'path> {
+    pub(super) fn new(file_path: &'path Path, input: &'input str) -> Self {
+        Self {
+            file_path,
+            input,
+            mark: Mark::default(),
+        }
+    }
+
+    fn error(&self, mark: Mark, reason: Error) -> MakeError {
+        MakeError::new_with(mark, self.file_path, reason)
+    }
+
+    fn take(&mut self, out: &mut Vec<Element>, kind: TokenKind, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let (text, input) = self.input.split_at(len);
+        self.input = input;
+        self.mark = advance(self.mark, text);
+        out.push(Element::Token(Token::new(kind, text)));
+    }
+
+    fn at_line_end(&self) -> bool {
+        self.input.is_empty() || self.input.starts_with('\n')
+    }
+
+    /// Takes the spaces and the comment up to the end of the line.
+    fn blank(&mut self, out: &mut Vec<Element>) {
+        let space = self.input.len() - self.input.trim_start_matches([' ', '\t']).len();
+        self.take(out, TokenKind::Space, space);
+        let (rest, _) = match_blank_line(self.input);
+        let comment = self.input.len() - rest.len();
+        self.take(out, TokenKind::Comment, comment);
+    }
+
+    fn line_end(&mut self, out: &mut Vec<Element>) -> ParseResult<()> {
+        self.blank(out);
+        match self.at_line_end() {
+            true => Ok(()),
+            false => Err(self.error(self.mark, ExpectedBlankLine)),
+        }
+    }
+
+    /// Takes the blank lines, including the line break before them unless it is the first line.
+    fn trivia(&mut self, out: &mut Vec<Element>, len: usize) {
+        let end = self.input.len() - len;
+        while self.input.len() > end {
+            match self.input.starts_with('\n') {
+                true => self.take(out, TokenKind::Newline, 1),
+                false => self.blank(out),
+            }
+        }
+    }
+
+    /// Finds the next line that is not blank.
+    ///
+    /// Returns the length of the text before the line and the line itself.
+    fn peek_line(&self, line_start: bool) -> Option<(usize, &'input str)> {
+        let mut offset = 0;
+        let mut rest = self.input;
+        if !line_start {
+            rest = rest.strip_prefix('\n')?;
+            offset = 1;
+        }
+        loop {
+            let line = rest.split('\n').next().unwrap_or_default();
+            if !is_blank(line) {
+                return Some((offset, line));
+            }
+            if line.len() == rest.len() {
+                return None;
+            }
+            offset += line.len() + 1;
+            rest = &rest[line.len() + 1..];
+        }
+    }
+
+    pub(super) fn document(mut self) -> ParseResult<CstNode> {
+        let mut children = Vec::new();
+        let (offset, line) = self
+            .peek_line(true)
+            .ok_or_else(|| self.error(self.mark, FailedDetermineType))?;
+        self.trivia(&mut children, offset);
+        if line.starts_with('\t') {
+            return Err(self.error(self.mark, ImpermissibleTab));
+        }
+        if line.starts_with(' ') {
+            return Err(self.error(self.mark, ImpermissibleSpace));
+        }
+        let node = match is_list_item(line) || key_len(line).is_some() {
+            true => self.block(0, true)?,
+            false => self.inline(0)?,
+        };
+        children.push(Element::Node(node));
+        let rest = self.input.len();
+        self.trivia(&mut children, rest);
+        match self.input.is_empty() {
+            true => Ok(CstNode::new(NodeKind::Document, 0, children)),
+            false => Err(self.error(self.mark, IncompleteDocument)),
+        }
+    }
+
+    /// Parses the node that is written after the prefix like `key:` or `-`.
+    fn value(&mut self, indent: usize) -> ParseResult<CstNode> {
+        let rest = self.input.strip_prefix(' ').unwrap_or(self.input);
+        if self.input.starts_with(' ') && !is_blank(rest.split('\n').next().unwrap_or_default()) {
+            let mut leading = Vec::new();
+            self.take(&mut leading, TokenKind::Space, 1);
+            let mut node = self.inline(indent)?;
+            leading.append(&mut node.children);
+            node.children = leading;
+            return Ok(node);
+        }
+        let mut leading = Vec::new();
+        self.line_end(&mut leading)?;
+        match self.peek_line(false) {
+            Some(_) => {
+                let mut node = self.block(indent, false)?;
+                leading.append(&mut node.children);
+                node.children = leading;
+                Ok(node)
+            }
+            None => Err(self.error(self.mark, FailedDetermineType)),
+        }
+    }
+
+    fn block(&mut self, indent: usize, line_start: bool) -> ParseResult<CstNode> {
+        let mut children = Vec::new();
+        let mut kind = None;
+        let mut line_start = line_start;
+        while let Some((offset, line)) = self.peek_line(line_start) {
+            let line_mark = advance(self.mark, &self.input[..offset]);
+            let line_tabs = tabs(line);
+            if line_tabs < indent {
+                break;
+            }
+            let content = &line[indent..];
+            if content.starts_with(' ') {
+                return Err(self.error(line_mark + Mark::new(0, indent), ImpermissibleSpace));
+            }
+            if line_tabs > indent {
+                return Err(self.error(line_mark + Mark::new(0, indent), ImpermissibleTab));
+            }
+            let entry_kind = match (is_list_item(content), key_len(content)) {
+                (true, _) => NodeKind::List,
+                (false, Some(_)) => NodeKind::Map,
+                (false, None) => {
+                    let reason = match kind {
+                        Some(NodeKind::List) => ExpectedListItem,
+                        Some(_) => ExpectedMapKey,
+                        None => FailedDetermineType,
+                    };
+                    return Err(self.error(line_mark + Mark::new(0, indent), reason));
+                }
+            };
+            match kind {
+                Some(NodeKind::List) if entry_kind != NodeKind::List => {
+                    return Err(self.error(line_mark + Mark::new(0, indent), ExpectedListItem))
+                }
+                Some(NodeKind::Map) if entry_kind != NodeKind::Map => {
+                    return Err(self.error(line_mark + Mark::new(0, indent), ExpectedMapKey))
+                }
+                _ => kind = Some(entry_kind),
+            }
+            self.trivia(&mut children, offset);
+            self.take(&mut children, TokenKind::Indent, indent);
+            let entry = match entry_kind {
+                NodeKind::List => self.list_item(indent)?,
+                _ => self.map_entry(indent)?,
+            };
+            children.push(Element::Node(entry));
+            line_start = false;
+        }
+        let kind = kind.ok_or_else(|| self.error(self.mark, FailedDetermineType))?;
+        Ok(CstNode::new(kind, indent, children))
+    }
+
+    fn list_item(&mut self, indent: usize) -> ParseResult<CstNode> {
+        let mut children = Vec::new();
+        self.take(&mut children, TokenKind::Dash, 1);
+        children.push(Element::Node(self.value(indent + 1)?));
+        Ok(CstNode::new(NodeKind::ListItem, indent, children))
+    }
+
+    fn map_entry(&mut self, indent: usize) -> ParseResult<CstNode> {
+        let mut children = Vec::new();
+        let len = key_len(self.input).ok_or_else(|| self.error(self.mark, ExpectedMapKey))?;
+        self.take(&mut children, TokenKind::Key, len);
+        self.take(&mut children, TokenKind::Colon, 1);
+        children.push(Element::Node(self.value(indent + 1)?));
+        Ok(CstNode::new(NodeKind::MapEntry, indent, children))
+    }
+
+    /// Parses the node written on the current line.
+    pub(super) fn inline(&mut self, indent: usize) -> ParseResult<CstNode> {
+        let mut children = Vec::new();
+        let input = self.input;
+        let kind = if input.starts_with("null") && is_blank(input[4..].split('\n').next().unwrap())
+        {
+            self.take(&mut children, TokenKind::Null, 4);
+            self.line_end(&mut children)?;
+            NodeKind::Null
+        } else if input.starts_with('"') {
+            let len = self.classic_len()?;
+            let text = &input[..len];
+            classic_string(self.file_path, text, indent, self.mark)?;
+            self.take(&mut children, TokenKind::String, len);
+            self.line_end(&mut children)?;
+            NodeKind::String
+        } else if input.starts_with(">>") {
+            let ((rest, _), _) = not_escaped_string(self.file_path, input, indent, self.mark)?;
+            self.take(&mut children, TokenKind::String, input.len() - rest.len());
+            NodeKind::String
+        } else if input.starts_with("> ") {
+            let len = input.find('\n').unwrap_or(input.len());
+            self.take(&mut children, TokenKind::String, len);
+            NodeKind::String
+        } else if input.starts_with("[]") {
+            self.take(&mut children, TokenKind::EmptyList, 2);
+            self.line_end(&mut children)?;
+            NodeKind::List
+        } else if input.starts_with("{}") {
+            self.take(&mut children, TokenKind::EmptyMap, 2);
+            self.line_end(&mut children)?;
+            NodeKind::Map
+        } else if input.starts_with("< ") {
+            self.file(&mut children, indent)?;
+            NodeKind::File
+        } else if input.starts_with('*') {
+            let len = 1 + self.name_len(1)?;
+            self.take(&mut children, TokenKind::GetAnchor, len);
+            self.line_end(&mut children)?;
+            NodeKind::GetAnchor
+        } else if input.starts_with('&') {
+            let len = 1 + self.name_len(1)?;
+            self.take(&mut children, TokenKind::TakeAnchor, len);
+            children.push(Element::Node(self.value(indent)?));
+            NodeKind::TakeAnchor
+        } else if input.starts_with("= ") {
+            self.take(&mut children, TokenKind::Equals, 1);
+            self.take(&mut children, TokenKind::Space, 1);
+            let line = self.input.split('\n').next().unwrap_or_default();
+            let len = key_len(line)
+                .filter(|_| !line.starts_with('"'))
+                .ok_or_else(|| self.error(self.mark, FailedDetermineType))?;
+            self.take(&mut children, TokenKind::Tag, len);
+            self.take(&mut children, TokenKind::Colon, 1);
+            children.push(Element::Node(self.value(indent)?));
+            NodeKind::Tagged
+        } else {
+            let ((rest, mark), _) = raw(self.file_path, input, self.mark)?;
+            if !(rest.is_empty() || rest.starts_with('\n')) {
+                return Err(self.error(mark, ExpectedBlankLine));
+            }
+            self.take(&mut children, TokenKind::Raw, input.len() - rest.len());
+            NodeKind::Raw
+        };
+        Ok(CstNode::new(kind, indent, children))
+    }
+
+    /// Gets the length of the classic string including both quotes.
+    fn classic_len(&self) -> ParseResult<usize> {
+        let mut iter = self.input.char_indices().skip(1);
+        while let Some((i, c)) = iter.next() {
+            match c {
+                '"' => return Ok(i + 1),
+                '\\' => {
+                    iter.next();
+                }
+                _ => {}
+            }
+        }
+        Err(self.error(advance(self.mark, self.input), IncompleteString))
+    }
+
+    fn name_len(&self, offset: usize) -> ParseResult<usize> {
+        let rest = &self.input[offset..];
+        let len = rest.find([' ', '\t', '\n']).unwrap_or(rest.len());
+        match len {
+            0 => Err(self.error(self.mark, FailedDetermineType)),
+            len => Ok(len),
+        }
+    }
+
+    fn file(&mut self, children: &mut Vec<Element>, indent: usize) -> ParseResult<()> {
+        self.take(children, TokenKind::FileMarker, 1);
+        self.take(children, TokenKind::Space, 1);
+        let line = self.input.split('\n').next().unwrap_or_default();
+        let end = [" # ", " #!"]
+            .iter()
+            .filter_map(|i| line.find(i))
+            .min()
+            .unwrap_or(line.len());
+        let len = line[..end].trim_end_matches([' ', '\t']).len();
+        if len == 0 {
+            return Err(self.error(self.mark, FailedDetermineType));
+        }
+        self.take(children, TokenKind::Path, len);
+        self.line_end(children)?;
+        if let Some((_, line)) = self.peek_line(false) {
+            if tabs(line) == indent && key_len(&line[indent..]).is_some() {
+                children.push(Element::Node(self.block(indent, false)?));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses the source as a single node written on one line, multiline strings continue with `indent`.
+///
+/// Errors refer to the file the node is to be written in.
+pub(super) fn parse_inline(file_path: &Path, source: &str, indent: usize) -> ParseResult<CstNode> {
+    let mut parser = Parser::new(file_path, source);
+    let node = parser.inline(indent)?;
+    match parser.input.is_empty() {
+        true => Ok(node),
+        false => Err(parser.error(parser.mark, IncompleteDocument)),
+    }
+}
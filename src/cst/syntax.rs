@@ -0,0 +1,260 @@
+use crate::data::node_path::quoted_key;
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Formatter},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenKind {
+    /// Spaces and tabs that are not an indentation.
+    Space,
+    /// Tabs at the beginning of a line with an entry.
+    Indent,
+    Newline,
+    Comment,
+    /// `-` of a list item.
+    Dash,
+    Key,
+    Colon,
+    /// `=` before a tag.
+    Equals,
+    Tag,
+    /// `&name`.
+    TakeAnchor,
+    /// `*name`.
+    GetAnchor,
+    /// `<` before a file path.
+    FileMarker,
+    Path,
+    Null,
+    Raw,
+    /// A string in any form, including its quotes or markers.
+    String,
+    /// `[]`.
+    EmptyList,
+    /// `{}`.
+    EmptyMap,
+}
+
+impl TokenKind {
+    /// Whether the token carries no meaning for the document.
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Space | TokenKind::Indent | TokenKind::Newline | TokenKind::Comment
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Token {
+    kind: TokenKind,
+    text: String,
+}
+
+impl Token {
+    pub fn new<S: Into<String>>(kind: TokenKind, text: S) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+        }
+    }
+
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        self.text.as_str()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NodeKind {
+    Document,
+    Null,
+    Raw,
+    String,
+    List,
+    ListItem,
+    Map,
+    MapEntry,
+    Tagged,
+    File,
+    TakeAnchor,
+    GetAnchor,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Element {
+    Token(Token),
+    Node(CstNode),
+}
+
+impl Display for Element {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Element::Token(i) => f.write_str(i.text()),
+            Element::Node(i) => write!(f, "{}", i),
+        }
+    }
+}
+
+/// Node of the concrete syntax tree.
+///
+/// Writing out all the tokens of the tree in order gives exactly the source text.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CstNode {
+    pub(super) kind: NodeKind,
+    pub(super) indent: usize,
+    pub(super) children: Vec<Element>,
+}
+
+impl CstNode {
+    pub(super) fn new(kind: NodeKind, indent: usize, children: Vec<Element>) -> Self {
+        Self {
+            kind,
+            indent,
+            children,
+        }
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    /// Number of tabs the contents of the node are indented with.
+    pub fn indent(&self) -> usize {
+        self.indent
+    }
+
+    pub fn children(&self) -> &[Element] {
+        self.children.as_slice()
+    }
+
+    pub fn tokens(&self) -> impl Iterator<Item = &Token> {
+        self.children.iter().filter_map(|i| match i {
+            Element::Token(i) => Some(i),
+            Element::Node(_) => None,
+        })
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &CstNode> {
+        self.children.iter().filter_map(|i| match i {
+            Element::Token(_) => None,
+            Element::Node(i) => Some(i),
+        })
+    }
+
+    pub(super) fn nodes_mut(&mut self) -> impl Iterator<Item = &mut CstNode> {
+        self.children.iter_mut().filter_map(|i| match i {
+            Element::Token(_) => None,
+            Element::Node(i) => Some(i),
+        })
+    }
+
+    pub(super) fn token(&self, kind: TokenKind) -> Option<&Token> {
+        self.tokens().find(|i| i.kind() == kind)
+    }
+
+    /// Gets the key of the map entry, without the quotes and escapes if it is quoted.
+    pub fn key(&self) -> Option<Cow<'_, str>> {
+        let text = match self.kind {
+            NodeKind::MapEntry => self.token(TokenKind::Key)?.text(),
+            _ => return None,
+        };
+        match text.starts_with('"') {
+            true => quoted_key(text).map(|(key, _)| Cow::Owned(key)),
+            false => Some(Cow::Borrowed(text)),
+        }
+    }
+
+    fn has_value(&self) -> bool {
+        matches!(
+            self.kind,
+            NodeKind::ListItem
+                | NodeKind::MapEntry
+                | NodeKind::Document
+                | NodeKind::Tagged
+                | NodeKind::TakeAnchor
+        )
+    }
+
+    /// Gets the value of the list item, map entry, document, tag or take anchor.
+    pub fn value(&self) -> Option<&CstNode> {
+        self.has_value().then(|| self.nodes().next()).flatten()
+    }
+
+    pub fn value_mut(&mut self) -> Option<&mut CstNode> {
+        match self.has_value() {
+            true => self.nodes_mut().next(),
+            false => None,
+        }
+    }
+
+    /// Gets the node under the document, tags and take anchors.
+    pub fn inner(&self) -> &CstNode {
+        match self.kind {
+            NodeKind::Document | NodeKind::Tagged | NodeKind::TakeAnchor => {
+                self.value().map_or(self, CstNode::inner)
+            }
+            _ => self,
+        }
+    }
+
+    pub fn inner_mut(&mut self) -> &mut CstNode {
+        match self.kind {
+            NodeKind::Document | NodeKind::Tagged | NodeKind::TakeAnchor => self
+                .value_mut()
+                .expect("Incorrect syntax tree structure, the node has no value.")
+                .inner_mut(),
+            _ => self,
+        }
+    }
+
+    /// Gets the value of the map entry by key, looking through tags and take anchors.
+    pub fn get(&self, key: &str) -> Option<&CstNode> {
+        let inner = self.inner();
+        match inner.kind {
+            NodeKind::Map => inner
+                .nodes()
+                .find(|i| i.key().as_deref() == Some(key))
+                .and_then(CstNode::value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut CstNode> {
+        let inner = self.inner_mut();
+        match inner.kind {
+            NodeKind::Map => inner
+                .nodes_mut()
+                .find(|i| i.key().as_deref() == Some(key))
+                .and_then(CstNode::value_mut),
+            _ => None,
+        }
+    }
+
+    /// Gets the item of the list by index, looking through tags and take anchors.
+    pub fn get_index(&self, index: usize) -> Option<&CstNode> {
+        let inner = self.inner();
+        match inner.kind {
+            NodeKind::List => inner.nodes().nth(index).and_then(CstNode::value),
+            _ => None,
+        }
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut CstNode> {
+        let inner = self.inner_mut();
+        match inner.kind {
+            NodeKind::List => inner.nodes_mut().nth(index).and_then(CstNode::value_mut),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CstNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.children.iter().try_for_each(|i| write!(f, "{}", i))
+    }
+}
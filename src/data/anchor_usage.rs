@@ -0,0 +1,223 @@
+use super::{
+    data::Data,
+    mark::Mark,
+    node::node::{FileNode, Node},
+    node_id::NodeId,
+    view::anchors::Anchors,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AnchorKind {
+    /// Taken in the document with `&name`.
+    Take,
+    /// Passed to an included file next to the `<` of the file.
+    File,
+}
+
+/// Place in the source where an anchor is taken or referred to.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AnchorSite {
+    /// Path of the file the place is written in.
+    pub file: PathBuf,
+    pub mark: Mark,
+    pub id: NodeId,
+}
+
+impl AnchorSite {
+    pub fn new(file: PathBuf, mark: Mark, id: NodeId) -> Self {
+        Self { file, mark, id }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AnchorInfo {
+    pub name: String,
+    pub kind: AnchorKind,
+    /// Where the anchor is taken, the id is of the node the anchor refers to.
+    pub definition: AnchorSite,
+    /// The `GetAnchor` nodes referring to the anchor, in the order of the document.
+    pub uses: Vec<AnchorSite>,
+    /// Index in `AnchorUsage::anchors` of the anchor with the same name available from a parent
+    /// file, which this anchor hides.
+    pub shadows: Option<usize>,
+}
+
+impl AnchorInfo {
+    fn new(name: String, kind: AnchorKind, definition: AnchorSite) -> Self {
+        Self {
+            name,
+            kind,
+            definition,
+            uses: Vec::new(),
+            shadows: None,
+        }
+    }
+
+    pub fn is_used(&self) -> bool {
+        !self.uses.is_empty()
+    }
+}
+
+/// Anchors of a document with the references to them, see `Data::anchor_usage`.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct AnchorUsage {
+    anchors: Vec<AnchorInfo>,
+}
+
+impl AnchorUsage {
+    /// Gets all anchors in the order of the document, the anchors passed to a file go before its
+    /// contents.
+    pub fn anchors(&self) -> &[AnchorInfo] {
+        self.anchors.as_slice()
+    }
+
+    /// Iterates over the anchors no `GetAnchor` refers to.
+    pub fn unused(&self) -> impl Iterator<Item = &AnchorInfo> {
+        self.anchors.iter().filter(|i| !i.is_used())
+    }
+
+    /// Iterates over the anchors hiding an anchor of a parent file, together with the hidden one.
+    pub fn shadowing(&self) -> impl Iterator<Item = (&AnchorInfo, &AnchorInfo)> {
+        let anchors = &self.anchors;
+        anchors
+            .iter()
+            .filter_map(|i| i.shadows.map(|j| (i, &anchors[j])))
+    }
+
+    pub(crate) fn new(data: &Data) -> Self {
+        let mut anchors = Vec::new();
+        // Anchors by the index of the node they refer to, and the references by the same index.
+        let mut targets = HashMap::new();
+        let mut references = Vec::new();
+        // Anchors with the index of the File the search for a hidden anchor starts from.
+        let mut scopes = Vec::new();
+
+        let mut stack = match data.data.is_empty() {
+            true => Vec::new(),
+            false => vec![(data.root(), Path::new(""), None)],
+        };
+        while let Some((index, file, scope)) = stack.pop() {
+            let node = data.get(index);
+            let site =
+                |id: usize, mark: Mark| AnchorSite::new(file.to_path_buf(), mark, NodeId(id));
+            match &node.node {
+                Node::File(i) => {
+                    for (name, j) in i.file_anchors.entries() {
+                        targets.insert(j, anchors.len());
+                        scopes.push(scope);
+                        let site = site(j, data.get(j).mark);
+                        anchors.push(AnchorInfo::new(name.clone(), AnchorKind::File, site));
+                    }
+                    stack.push((i.node_index, i.path.as_path(), Some(index)));
+                    let file_anchors = i.file_anchors.entries().map(|(_, j)| (j, file, scope));
+                    stack.extend(file_anchors.collect::<Vec<_>>().into_iter().rev());
+                    continue;
+                }
+                Node::TakeAnchor(i) => {
+                    targets.insert(i.node_index, anchors.len());
+                    scopes.push(scope.and_then(|i| file_node(data, i).parent));
+                    anchors.push(AnchorInfo::new(
+                        i.name.clone(),
+                        AnchorKind::Take,
+                        site(i.node_index, node.mark),
+                    ));
+                }
+                Node::GetAnchor(i) => references.push((i.node_index, site(index, node.mark))),
+                _ => {}
+            }
+            let children = data.children(index).into_iter().rev();
+            stack.extend(children.map(|i| (i, file, scope)));
+        }
+
+        for (target, site) in references {
+            if let Some(i) = targets.get(&target) {
+                anchors[*i].uses.push(site);
+            }
+        }
+        for (anchor, scope) in anchors.iter_mut().zip(scopes) {
+            let hidden = scope.and_then(|i| {
+                let anchors = Anchors::new(
+                    Mark::default(),
+                    file_node(data, i),
+                    data,
+                    (),
+                    Default::default(),
+                );
+                anchors.get_index(anchor.name.as_str())
+            });
+            anchor.shadows = hidden.and_then(|i| targets.get(&i).copied());
+        }
+        Self { anchors }
+    }
+}
+
+fn file_node(data: &Data, index: usize) -> &FileNode {
+    match &data.get(index).node {
+        Node::File(i) => i,
+        _ => panic!("Incorrect document structure, the node is not a File."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cst::document::Cst, test_dir::TestDir};
+
+    #[test]
+    fn test_anchor_usage() {
+        let dir = TestDir::new("anchor_usage");
+        let include = "value: *alias\nother: *name\nunused: &unused 2\n";
+        std::fs::write(dir.join("include.ieml"), include).unwrap();
+        let source = "name: &name main
+unused: &unused 1
+include: < include.ieml
+	alias: *name
+	port: 80
+copy: *name
+";
+        let (main, include) = (dir.join("main.ieml"), dir.join("include.ieml"));
+        let data = Cst::parse(&main, source).unwrap().to_data().unwrap();
+        let usage = data.anchor_usage();
+
+        let anchors = usage
+            .anchors()
+            .iter()
+            .map(|i| (i.name.as_str(), i.kind, i.definition.file.as_path()))
+            .collect::<Vec<_>>();
+        let expected = [
+            ("name", AnchorKind::Take, main.as_path()),
+            ("unused", AnchorKind::Take, main.as_path()),
+            ("alias", AnchorKind::File, main.as_path()),
+            ("port", AnchorKind::File, main.as_path()),
+            ("unused", AnchorKind::Take, include.as_path()),
+        ];
+        assert_eq!(anchors, expected);
+
+        let name = &usage.anchors()[0];
+        assert_eq!(name.definition.mark, Mark::new(0, 6));
+        let view = data.view_at(name.definition.id).unwrap();
+        assert_eq!(view.raw().unwrap().raw(), "main");
+        let uses = name
+            .uses
+            .iter()
+            .map(|i| (i.file.as_path(), i.mark))
+            .collect::<Vec<_>>();
+        let expected = [
+            (main.as_path(), Mark::new(3, 8)),
+            (include.as_path(), Mark::new(1, 7)),
+            (main.as_path(), Mark::new(5, 6)),
+        ];
+        assert_eq!(uses, expected);
+        assert!(data.view_at(name.uses[0].id).unwrap().is_get_anchor());
+        assert_eq!(usage.anchors()[2].uses[0].file, include);
+
+        let unused = usage.unused().map(|i| i.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(unused, ["unused", "port", "unused"]);
+        let shadowing = usage.shadowing().collect::<Vec<_>>();
+        assert_eq!(shadowing, [(&usage.anchors()[4], &usage.anchors()[1])]);
+    }
+}
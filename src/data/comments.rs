@@ -0,0 +1,19 @@
+/// Comments written around a node, as they are written including `#`.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Comments {
+    /// Comment lines before the list item or map entry.
+    pub leading: Vec<String>,
+    /// The comment at the end of the first line of the node, for the root node also the comment
+    /// lines at the end of the document.
+    pub trailing: Vec<String>,
+}
+
+impl Comments {
+    pub fn new(leading: Vec<String>, trailing: Vec<String>) -> Self {
+        Self { leading, trailing }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_empty()
+    }
+}
@@ -1,11 +1,38 @@
-use super::node::node::MarkedNode;
+use super::node::node::{MarkedNode, Node};
 use super::view::analyse_anchors::AnalyseAnchors;
 use super::view::view::View;
-use std::fmt;
+use super::view::view_mut::ViewMut;
+use crate::emit::emitter::emit;
+use super::comments::Comments;
+use super::error::InvalidStructureError;
+use super::anchor_usage::AnchorUsage;
+use super::error::marked;
+use super::make::init::find_anchor_cycle;
+use super::expansion::{expansions, Expansion};
+use super::mark::{Mark, Span};
+use super::materialize::{MaterializeOptions, Materializer};
+use super::node_id::NodeId;
+use super::node_path::NodePath;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 
-#[derive(Clone, PartialEq, Eq, Default)]
+#[derive(Clone, Default)]
 pub struct Data {
     pub(crate) data: Vec<MarkedNode>,
+    pub(crate) comments: HashMap<usize, Comments>,
+    /// Index of the parent of every node, built on the first request and dropped on editing.
+    parents: OnceLock<Vec<Option<usize>>>,
+    /// Index of the File every node is written in, built like `parents`.
+    files: OnceLock<Vec<Option<usize>>>,
+    /// Spans of the nodes of every file sorted by the beginning, built like `parents`.
+    spans: OnceLock<HashMap<PathBuf, Vec<(Span, usize)>>>,
+    /// Measures of every node with the anchors expanded, built like `parents`.
+    expansions: OnceLock<Vec<Expansion>>,
 }
 
 impl Data {
@@ -13,6 +40,11 @@ impl Data {
     pub(crate) fn new<const N: usize>(data: [MarkedNode; N]) -> Self {
         Self {
             data: Vec::from(data),
+            comments: HashMap::new(),
+            parents: OnceLock::new(),
+            files: OnceLock::new(),
+            spans: OnceLock::new(),
+            expansions: OnceLock::new(),
         }
     }
 
@@ -23,29 +55,387 @@ impl Data {
     }
 
     pub(crate) fn get_mut(&mut self, index: usize) -> &mut MarkedNode {
+        self.drop_indexes();
         self.data
             .get_mut(index)
             .expect("Incorrect document structure, node does not exist.")
     }
 
+    pub(crate) fn root(&self) -> usize {
+        self.data
+            .len()
+            .checked_sub(1)
+            .expect("Incorrect document structure, node does not exist.")
+    }
+
+    /// Finds the index of the File node in which the node with the given index is written.
+    ///
+    /// Returns `None` if the node is not inside any File, for example, if it is the root.
+    pub(crate) fn file_index(&self, index: usize) -> Option<usize> {
+        let files = self.files.get_or_init(|| {
+            let mut files = vec![None; self.data.len()];
+            let mut stack = match self.data.is_empty() {
+                true => Vec::new(),
+                false => vec![(self.root(), None)],
+            };
+            while let Some((current, file_index)) = stack.pop() {
+                files[current] = file_index;
+                match &self.get(current).node {
+                    Node::File(i) => {
+                        stack.extend(i.file_anchors.entries().map(|(_, i)| (i, file_index)));
+                        stack.push((i.node_index, Some(current)));
+                    }
+                    _ => stack.extend(self.children(current).into_iter().map(|i| (i, file_index))),
+                }
+            }
+            files
+        });
+        files.get(index).copied().flatten()
+    }
+
+    /// Gets the indexes of the nodes directly under the node in the order of the source, the node a
+    /// `GetAnchor` refers to is not under it.
+    pub(crate) fn children(&self, index: usize) -> Vec<usize> {
+        match &self.get(index).node {
+            Node::List(i) => i.data.clone(),
+            Node::Map(i) => i.entries().map(|(_, i)| i).collect(),
+            Node::Tagged(i) => vec![i.node_index],
+            Node::File(i) => {
+                let mut result = i.file_anchors.entries().map(|(_, i)| i).collect::<Vec<_>>();
+                result.push(i.node_index);
+                result
+            }
+            Node::TakeAnchor(i) => vec![i.node_index],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Gets the index of the node the node is directly under, `None` for the root and for the nodes
+    /// that are not reachable from it.
+    pub(crate) fn parent(&self, index: usize) -> Option<usize> {
+        let parents = self.parents.get_or_init(|| {
+            let mut parents = vec![None; self.data.len()];
+            let mut stack = match self.data.is_empty() {
+                true => Vec::new(),
+                false => vec![self.root()],
+            };
+            while let Some(current) = stack.pop() {
+                for i in self.children(current) {
+                    parents[i] = Some(current);
+                    stack.push(i);
+                }
+            }
+            parents
+        });
+        parents.get(index).copied().flatten()
+    }
+
+    pub(crate) fn expansion(&self, index: usize) -> Expansion {
+        self.expansions.get_or_init(|| expansions(self))[index]
+    }
+
+    fn drop_indexes(&mut self) {
+        self.parents.take();
+        self.files.take();
+        self.spans.take();
+        self.expansions.take();
+    }
+
+    fn spans(&self) -> HashMap<PathBuf, Vec<(Span, usize)>> {
+        let mut result = HashMap::<PathBuf, Vec<_>>::new();
+        let mut stack = match self.data.is_empty() {
+            true => Vec::new(),
+            false => vec![(self.root(), Path::new(""))],
+        };
+        while let Some((index, file)) = stack.pop() {
+            let node = self.get(index);
+            if let Some(end) = node.end {
+                let spans = result.entry(file.to_path_buf()).or_default();
+                spans.push((Span::new(node.mark, end), index));
+            }
+            match &node.node {
+                Node::File(i) => {
+                    stack.extend(i.file_anchors.entries().map(|(_, i)| (i, file)));
+                    stack.push((i.node_index, i.path.as_path()));
+                }
+                _ => stack.extend(self.children(index).into_iter().map(|i| (i, file))),
+            }
+        }
+        let key = |mark: Mark| (mark.line, mark.symbol);
+        for spans in result.values_mut() {
+            spans.sort_by_key(|(span, _)| (key(span.begin), Reverse(key(span.end))));
+        }
+        result
+    }
+
+    /// Gets the indexes of the node and all the nodes under it.
+    pub(crate) fn descendants(&self, index: usize) -> HashSet<usize> {
+        let mut result = HashSet::new();
+        let mut stack = vec![index];
+        while let Some(current) = stack.pop() {
+            if result.insert(current) {
+                stack.extend(self.children(current));
+            }
+        }
+        result
+    }
+
+    /// Adds the node at the end, returns its index.
+    pub(crate) fn push(&mut self, node: MarkedNode) -> usize {
+        self.drop_indexes();
+        self.data.push(node);
+        self.root()
+    }
+
+    /// Moves the node to the end, its old place is left unused.
+    ///
+    /// Files included in the moved node keep referring to it, other references have to be
+    /// updated by the caller. Returns the new index of the node.
+    pub(crate) fn move_node(&mut self, index: usize) -> usize {
+        let node = std::mem::take(self.get_mut(index));
+        let new_index = self.push(node);
+        for i in self.data.iter_mut() {
+            if let Node::File(file) = &mut i.node {
+                if file.parent == Some(index) {
+                    file.parent = Some(new_index);
+                }
+            }
+        }
+        new_index
+    }
+
+    /// Makes the references to the node at `from` refer to the node at `to`, the references of
+    /// the node at `to` itself excepted.
+    ///
+    /// Files keep referring to the file they are written in as their parent.
+    pub(crate) fn redirect(&mut self, from: usize, to: usize) {
+        self.drop_indexes();
+        for (_, i) in self.data.iter_mut().enumerate().filter(|(i, _)| *i != to) {
+            let parent = match &i.node {
+                Node::File(file) => file.parent,
+                _ => None,
+            };
+            i.node.remap(|i| if i == from { to } else { i });
+            if let Node::File(file) = &mut i.node {
+                file.parent = parent;
+            }
+        }
+    }
+
+    /// Moves the root back to the end after nodes were added behind it.
+    pub(crate) fn move_root(&mut self, root: usize) {
+        if root == self.root() {
+            return;
+        }
+        let new_index = self.move_node(root);
+        if let Some(comments) = self.comments.remove(&root) {
+            self.comments.insert(new_index, comments);
+        }
+    }
+
+    /// Checks that the nodes reachable from the root form a tree, that every reference refers to a
+    /// node of the tree, that every file refers to the file it is written in as its parent and that
+    /// no anchor refers to itself through the nodes it holds.
+    pub fn validate(&self) -> Result<(), InvalidStructureError> {
+        if self.data.is_empty() {
+            return Ok(());
+        }
+        let len = self.data.len();
+        let mut reached = vec![false; len];
+        let mut stack = vec![(self.root(), None)];
+        while let Some((index, file)) = stack.pop() {
+            if std::mem::replace(&mut reached[index], true) {
+                return Err(InvalidStructureError::SharedNode { index });
+            }
+            let children = self.children(index);
+            if let Some(node_index) = children.iter().copied().find(|i| *i >= len) {
+                return Err(InvalidStructureError::MissingNode { index, node_index });
+            }
+            let content = match &self.get(index).node {
+                Node::File(i) if i.parent != file => {
+                    return Err(InvalidStructureError::InvalidParent { index })
+                }
+                Node::File(i) => Some(i.node_index),
+                _ => None,
+            };
+            stack.extend(children.into_iter().map(|i| match Some(i) == content {
+                true => (i, Some(index)),
+                false => (i, file),
+            }));
+        }
+
+        let is_reached = |i: usize| reached.get(i).copied().unwrap_or(false);
+        for (index, node) in self.data.iter().enumerate().filter(|(i, _)| reached[*i]) {
+            let anchors = match &node.node {
+                Node::GetAnchor(i) => vec![(&i.name, i.node_index)],
+                Node::File(i) => i.anchors.entries().collect(),
+                _ => Vec::new(),
+            };
+            if let Some((name, _)) = anchors.into_iter().find(|(_, i)| !is_reached(*i)) {
+                let name = name.clone();
+                return Err(InvalidStructureError::UnreachableAnchor { index, name });
+            }
+        }
+
+        let get_anchors = (0..len)
+            .filter(|i| reached[*i] && matches!(self.get(*i).node, Node::GetAnchor(_)));
+        if let Some(cycle) = find_anchor_cycle(self, get_anchors) {
+            let index = cycle[cycle.len() - 2];
+            let name = match &self.get(index).node {
+                Node::GetAnchor(i) => i.name.clone(),
+                _ => panic!("Incorrect document structure, the node is not a GetAnchor."),
+            };
+            return Err(InvalidStructureError::AnchorCycle { index, name });
+        }
+        Ok(())
+    }
+
+    /// Drops the nodes that are not reachable from the root, for example, the ones left by
+    /// editing, and lays the rest out with the nodes under a node before it and the root last.
+    ///
+    /// The document is checked before the compaction and is not changed if it is invalid.
+    pub fn compact(&mut self) -> Result<(), InvalidStructureError> {
+        self.validate()?;
+        self.drop_indexes();
+        if self.data.is_empty() {
+            return Ok(());
+        }
+        let mut order = Vec::new();
+        let mut stack = vec![(self.root(), false)];
+        while let Some((index, visited)) = stack.pop() {
+            if visited {
+                order.push(index);
+                continue;
+            }
+            stack.push((index, true));
+            let children = self.children(index).into_iter().rev();
+            stack.extend(children.map(|i| (i, false)));
+        }
+
+        let mut new_index = vec![None; self.data.len()];
+        for (new, old) in order.iter().enumerate() {
+            new_index[*old] = Some(new);
+        }
+        let remap = |i: usize| {
+            new_index[i].expect("Incorrect document structure, the node is not reachable.")
+        };
+        let mut data = Vec::with_capacity(order.len());
+        for i in order {
+            let mut node = std::mem::take(self.get_mut(i));
+            node.node.remap(remap);
+            data.push(node);
+        }
+        self.data = data;
+        self.comments = std::mem::take(&mut self.comments)
+            .into_iter()
+            .filter_map(|(i, comments)| Some((new_index.get(i).copied().flatten()?, comments)))
+            .collect();
+        debug_assert_eq!(self.validate(), Ok(()));
+        Ok(())
+    }
+
+    /// Drops the nodes starting from the index together with their comments.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.drop_indexes();
+        self.data.truncate(len);
+        self.comments.retain(|i, _| *i < len);
+    }
+
+    /// Finds the innermost node whose span covers the position in the file, together with its
+    /// path from the root.
+    ///
+    /// The file is the path the file was read from, the line and the column count from zero, as
+    /// in `Mark`. Only the nodes read from the source have spans.
+    pub fn node_at(
+        &self,
+        file: &Path,
+        line: usize,
+        column: usize,
+    ) -> Option<(NodePath, View<'_>)> {
+        let key = |mark: Mark| (mark.line, mark.symbol);
+        let position = (line, column);
+        let spans = self.spans.get_or_init(|| self.spans()).get(file)?;
+        let count = spans.partition_point(|(span, _)| key(span.begin) <= position);
+        let (mut span, mut index) = *spans.get(count.checked_sub(1)?)?;
+        // The covering nodes hold the node found, so the innermost of them is its closest ancestor
+        // that covers the position.
+        while position >= key(span.end) {
+            let parent = self.parent(index)?;
+            let node = self.get(parent);
+            match (&node.node, node.end) {
+                (Node::File(i), _) if i.node_index == index => return None,
+                (_, Some(end)) => span = Span::new(node.mark, end),
+                (_, None) => {}
+            }
+            index = parent;
+        }
+        let view = View::new(index, self, (), Default::default()).with_parent_segment();
+        let mut path = std::iter::once(view.clone())
+            .chain(view.ancestors())
+            .filter_map(|i| i.key_in_parent())
+            .collect::<Vec<_>>();
+        path.reverse();
+        Some((NodePath::from(path), view))
+    }
+
+    /// Finds the anchors of the document, the references to each of them, and the anchors hiding
+    /// anchors of parent files.
+    pub fn anchor_usage(&self) -> AnchorUsage {
+        AnchorUsage::new(self)
+    }
+
+    /// Makes a copy of the document without anchors: every `GetAnchor` is replaced with a copy of
+    /// the node it refers to, every `TakeAnchor` with the node under it, and the anchors passed to
+    /// files are left out.
+    ///
+    /// Fails if the copy would have more nodes than `options.max_nodes`, the error is marked with
+    /// the reference being expanded at that moment.
+    pub fn materialize(&self, options: MaterializeOptions) -> Result<Data, marked::TooLargeError> {
+        Materializer::new(self, options).materialize()
+    }
+
+    /// Gets the view of the node with the handle, `None` if the node is no longer in the document.
+    ///
+    /// The view knows the anchors available to the node, as the view reached from the root does.
+    pub fn view_at(&self, id: NodeId) -> Option<View<'_>> {
+        let NodeId(index) = id;
+        let found = index < self.data.len()
+            && (index == self.root() || self.parent(index).is_some());
+        found.then(|| View::new(index, self, (), Default::default()).with_parent_segment())
+    }
+
     pub fn view(&self) -> View {
-        View::new(
-            self.data
-                .last()
-                .expect("Incorrect document structure, node does not exist."),
-            self,
-            ()
-        )
+        View::new(self.root(), self, (), Default::default())
+    }
+
+    /// Gets the view for editing the value of the document.
+    ///
+    /// The `File` at the root is not edited, the view is of the node in it.
+    pub fn view_mut(&mut self) -> ViewMut<'_> {
+        let root = self.root();
+        let index = match &self.get(root).node {
+            Node::File(i) => i.node_index,
+            _ => root,
+        };
+        ViewMut::new(index, self)
     }
 
     pub fn view_with_analyse<'data, A: AnalyseAnchors<'data>>(&'data self, anchor_analyser: A) -> View<'data, A> {
-        View::new(
-            self.data
-                .last()
-                .expect("Incorrect document structure, node does not exist."),
-            self,
-            anchor_analyser
-        )
+        View::new(self.root(), self, anchor_analyser, Default::default())
+    }
+}
+
+impl PartialEq for Data {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data && self.comments == other.comments
+    }
+}
+
+impl Eq for Data {}
+
+impl fmt::Display for Data {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", emit(self, Default::default()))
     }
 }
 
@@ -54,3 +444,192 @@ impl fmt::Debug for Data {
         write!(f, "{:?}", self.view())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        make,
+        mark::Mark,
+        node::node::{GetAnchorNode, ListNode},
+        node_path::PathSegment,
+        value::Value,
+    };
+    use super::*;
+    use crate::{cst::document::Cst, test_dir::TestDir};
+    use std::convert::Infallible;
+
+    const SOURCE: &str = "# ports
+port: 8080
+motd: &motd > hello
+copy: *motd
+list:
+	- a # first
+	- b
+";
+
+    #[test]
+    fn test_compact() {
+        let mut data = Cst::parse("test.ieml", SOURCE).unwrap().to_data().unwrap();
+        let mut root = data.view_mut();
+        root.get("port").unwrap().set(Value::Raw("9090".into())).unwrap();
+        root.get("motd").unwrap().inner().set("bye").unwrap();
+        root.get("list").unwrap().remove_index(1).unwrap();
+        root.insert("name", make::string::<_, Infallible, _>(Mark::default(), (), "main"))
+            .unwrap();
+        root.get("name").unwrap().wrap_tag("name");
+
+        let text = data.to_string();
+        let len = data.data.len();
+        assert_eq!(data.validate(), Ok(()));
+        assert_eq!(data.compact(), Ok(()));
+        assert!(data.data.len() < len);
+        assert_eq!(data.to_string(), text);
+        assert!(data.view().is_file());
+        let copy = data.view().map().unwrap().get("copy").unwrap();
+        let copy = copy.clear_step_get_anchor().unwrap();
+        assert_eq!(copy.string().unwrap().string(), "bye");
+
+        let len = data.data.len();
+        assert_eq!(data.compact(), Ok(()));
+        assert_eq!(data.data.len(), len);
+        assert_eq!(data.to_string(), text);
+    }
+
+    #[test]
+    fn test_compact_file() {
+        let dir = TestDir::new("compact_file");
+        std::fs::write(dir.join("include.ieml"), "value: *name\n").unwrap();
+        let source = "name: &name > main\ninclude: < include.ieml\n\tport: 80\n";
+        let mut data = Cst::parse(dir.join("main.ieml"), source)
+            .unwrap()
+            .to_data()
+            .unwrap();
+        let mut root = data.view_mut();
+        root.insert("other", make::null::<_, Infallible>(Mark::default(), ()))
+            .unwrap();
+        root.get("other").unwrap().set(Value::Raw("1".into())).unwrap();
+        assert_eq!(data.compact(), Ok(()));
+
+        let include = data.view().map().unwrap().get("include").unwrap();
+        let file = include.file().unwrap();
+        let parent = file.anchors().parent().unwrap();
+        assert_eq!(parent.get("name").unwrap().string().unwrap().string(), "main");
+        let port = file.anchors().get("port").unwrap();
+        assert_eq!(port.raw().unwrap().raw(), "80");
+        let value = file.view().map().unwrap().get("value").unwrap();
+        let value = value.clear_step_get_anchor().unwrap();
+        assert_eq!(value.string().unwrap().string(), "main");
+    }
+
+    #[test]
+    fn test_node_at() {
+        let data = Cst::parse("test.ieml", SOURCE).unwrap().to_data().unwrap();
+        let file = Path::new("test.ieml");
+        let (path, view) = data.node_at(file, 1, 7).unwrap();
+        assert_eq!(path.to_string(), "port");
+        assert_eq!(view.raw().unwrap().raw(), "8080");
+        let (path, view) = data.node_at(file, 6, 3).unwrap();
+        assert_eq!(path.to_string(), "list[1]");
+        assert_eq!(view.raw().unwrap().raw(), "b");
+        let (path, view) = data.node_at(file, 5, 1).unwrap();
+        assert_eq!(path.to_string(), "list");
+        assert!(view.is_list());
+        let (path, view) = data.node_at(file, 2, 7).unwrap();
+        assert_eq!(path.to_string(), "motd");
+        assert!(view.is_take_anchor());
+        assert!(data.node_at(file, 7, 0).is_none());
+        assert!(data.node_at(Path::new("other.ieml"), 1, 7).is_none());
+
+        let dir = TestDir::new("node_at");
+        std::fs::write(dir.join("include.ieml"), "first: 1\nsecond: 2\n").unwrap();
+        let source = "name: main\ninclude: < include.ieml\n";
+        let data = Cst::parse(dir.join("main.ieml"), source)
+            .unwrap()
+            .to_data()
+            .unwrap();
+        let (path, view) = data.node_at(&dir.join("include.ieml"), 1, 8).unwrap();
+        assert_eq!(path.to_string(), "include.second");
+        assert_eq!(view.raw().unwrap().raw(), "2");
+        let (path, view) = data.node_at(&dir.join("main.ieml"), 0, 6).unwrap();
+        assert_eq!(path.to_string(), "name");
+        assert_eq!(view.raw().unwrap().raw(), "main");
+    }
+
+    #[test]
+    fn test_view_at() {
+        let mut data = Cst::parse("test.ieml", SOURCE).unwrap().to_data().unwrap();
+        let root = data.view();
+        assert_eq!(data.view_at(root.id()), Some(root));
+        let map = data.view().clear_step_file().unwrap().map().unwrap();
+        let copy = map.get("copy").unwrap().id();
+        let second = map.get("list").unwrap().list().unwrap().get(1).unwrap().id();
+
+        let view = data.view_at(copy).unwrap();
+        assert_eq!(view.key_in_parent(), Some(PathSegment::Key("copy".into())));
+        let view = view.clear_step_get_anchor().unwrap();
+        assert_eq!(view.string().unwrap().string(), "hello");
+
+        let mut root = data.view_mut();
+        root.get("list").unwrap().remove_index(1).unwrap();
+        assert_eq!(data.view_at(second), None);
+        assert!(data.view_at(copy).is_some());
+        assert_eq!(data.view_at(NodeId(data.data.len())), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mark = Mark::default();
+        let node = |node| MarkedNode::new(node, mark);
+
+        let mut data = Data::new([node(Node::List(ListNode::new(vec![3])))]);
+        let error = InvalidStructureError::MissingNode {
+            index: 0,
+            node_index: 3,
+        };
+        assert_eq!(data.validate(), Err(error));
+        let before = data.clone();
+        assert!(data.compact().is_err());
+        assert_eq!(data, before);
+
+        let data = Data::new([
+            node(Node::Null),
+            node(Node::List(ListNode::new(vec![0, 0]))),
+        ]);
+        let error = InvalidStructureError::SharedNode { index: 0 };
+        assert_eq!(data.validate(), Err(error));
+
+        let data = Data::new([
+            node(Node::Null),
+            node(Node::GetAnchor(GetAnchorNode::new("a".into(), 0))),
+            node(Node::List(ListNode::new(vec![1]))),
+        ]);
+        let error = InvalidStructureError::UnreachableAnchor {
+            index: 1,
+            name: "a".into(),
+        };
+        assert_eq!(data.validate(), Err(error));
+
+        let data = Data::new([
+            node(Node::List(ListNode::new(vec![1]))),
+            node(Node::GetAnchor(GetAnchorNode::new("a".into(), 0))),
+            node(Node::List(ListNode::new(vec![0]))),
+        ]);
+        let error = InvalidStructureError::AnchorCycle {
+            index: 1,
+            name: "a".into(),
+        };
+        assert_eq!(data.validate(), Err(error));
+
+        let mut data = Data::new([
+            node(Node::Null),
+            node(Node::Raw("a".into())),
+            node(Node::List(ListNode::new(vec![1]))),
+        ]);
+        assert_eq!(data.compact(), Ok(()));
+        let expected = Data::new([
+            node(Node::Raw("a".into())),
+            node(Node::List(ListNode::new(vec![0]))),
+        ]);
+        assert_eq!(data, expected);
+    }
+}
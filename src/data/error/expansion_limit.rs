@@ -0,0 +1,51 @@
+use std::fmt::{Display, Formatter};
+
+/// Measure of a node with the anchors expanded that an `ExpansionLimits` bounds.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExpansionLimit {
+    Nodes,
+    Dereferences,
+    Depth,
+}
+
+impl Display for ExpansionLimit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpansionLimit::Nodes => write!(f, "nodes"),
+            ExpansionLimit::Dereferences => write!(f, "anchor dereferences"),
+            ExpansionLimit::Depth => write!(f, "levels of nesting"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct ExpansionLimitError {
+    limit: ExpansionLimit,
+    max: usize,
+}
+
+impl ExpansionLimitError {
+    pub fn new(limit: ExpansionLimit, max: usize) -> Self {
+        Self { limit, max }
+    }
+
+    pub fn get_limit(&self) -> ExpansionLimit {
+        self.limit
+    }
+
+    pub fn get_max(&self) -> usize {
+        self.max
+    }
+}
+
+impl Display for ExpansionLimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The node with the anchors expanded has more than {} {}.",
+            self.max, self.limit
+        )
+    }
+}
+
+impl std::error::Error for ExpansionLimitError {}
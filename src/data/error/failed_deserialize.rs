@@ -1,4 +1,4 @@
-use crate::data::error::marked;
+use crate::data::{error::marked, node_path::NodePath};
 use std::{
     any::type_name,
     error::Error,
@@ -8,13 +8,15 @@ use std::{
 #[derive(PartialEq, Eq, Debug)]
 pub struct FailedDeserializeError<E: Error + PartialEq + Eq> {
     type_name: &'static str,
+    path: NodePath,
     reason: Box<marked::DeserializeError<E>>,
 }
 
 impl<E: Error + PartialEq + Eq> FailedDeserializeError<E> {
-    pub fn new<T>(reason: Box<marked::DeserializeError<E>>) -> Self {
+    pub fn new<T>(path: NodePath, reason: Box<marked::DeserializeError<E>>) -> Self {
         Self {
             type_name: type_name::<T>(),
+            path,
             reason,
         }
     }
@@ -23,25 +25,72 @@ impl<E: Error + PartialEq + Eq> FailedDeserializeError<E> {
         self.type_name
     }
 
+    /// Gets the path from the view that was decoded to the node that failed to decode.
+    pub fn get_path(&self) -> &NodePath {
+        &self.path
+    }
+
+    /// Gets the path from the view that was decoded to the deepest node that failed to decode.
+    pub fn get_full_path(&self) -> NodePath {
+        match &*self.reason {
+            marked::DeserializeError::FailedDecode(i) => self.path.join(&i.data.get_full_path()),
+            _ => self.path.clone(),
+        }
+    }
+
     pub fn get_reason(&self) -> &Box<marked::DeserializeError<E>> {
         &self.reason
     }
+
+    /// Splits the error into errors each of which has a single failure at the end of the chain of reasons.
+    pub fn split(self) -> Vec<Self> {
+        let Self {
+            type_name,
+            path,
+            reason,
+        } = self;
+        reason
+            .split()
+            .into_iter()
+            .map(|reason| Self {
+                type_name,
+                path: path.clone(),
+                reason: Box::new(reason),
+            })
+            .collect()
+    }
 }
 
-impl<E: Error + PartialEq + Eq> Display for FailedDeserializeError<E> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match *self.reason {
+impl<E: Error + PartialEq + Eq> FailedDeserializeError<E> {
+    /// Writes the chain of reasons without the paths, the full path written before it covers them.
+    fn fmt_chain(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let type_name = self.get_type_name();
+        match &*self.reason {
             marked::DeserializeError::Failed => {
-                write!(f, "Failed to convert view to '{}'.", self.get_type_name())
+                write!(f, "Failed to convert view to '{}'.", type_name)
+            }
+            marked::DeserializeError::FailedDecode(i) => {
+                writeln!(f, "Failed to convert view to '{}', because:", type_name)?;
+                write!(f, "{}:{}: ", i.mark.line, i.mark.symbol)?;
+                i.data.fmt_chain(f)
             }
-            _ => write!(
+            reason => write!(
                 f,
                 "Failed to convert view to '{}', because:\n{}",
-                self.get_type_name(),
-                *self.reason
+                type_name, reason
             ),
         }
     }
 }
 
+impl<E: Error + PartialEq + Eq> Display for FailedDeserializeError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let path = self.get_full_path();
+        if !path.is_empty() {
+            write!(f, "{}: ", path)?;
+        }
+        self.fmt_chain(f)
+    }
+}
+
 impl<E: Error + PartialEq + Eq> Error for FailedDeserializeError<E> {}
@@ -0,0 +1,34 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct InvalidPathError {
+    path: String,
+    position: usize,
+}
+
+impl InvalidPathError {
+    pub fn new(path: String, position: usize) -> Self {
+        Self { path, position }
+    }
+
+    pub fn get_path(&self) -> &String {
+        &self.path
+    }
+
+    /// Gets the byte offset in the path at which the invalid segment begins.
+    pub fn get_position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for InvalidPathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The path '{}' is not valid at the position {}.",
+            self.path, self.position
+        )
+    }
+}
+
+impl std::error::Error for InvalidPathError {}
@@ -0,0 +1,34 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct InvalidSelectorError {
+    selector: String,
+    position: usize,
+}
+
+impl InvalidSelectorError {
+    pub fn new(selector: String, position: usize) -> Self {
+        Self { selector, position }
+    }
+
+    pub fn get_selector(&self) -> &String {
+        &self.selector
+    }
+
+    /// Gets the byte offset in the selector at which the invalid step begins.
+    pub fn get_position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for InvalidSelectorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The selector '{}' is not valid at the position {}.",
+            self.selector, self.position
+        )
+    }
+}
+
+impl std::error::Error for InvalidSelectorError {}
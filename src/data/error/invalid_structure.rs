@@ -0,0 +1,48 @@
+use std::fmt::{Display, Formatter};
+
+/// Violation of the structure of a document, `index` is the index of the offending node.
+#[derive(PartialEq, Eq, Debug)]
+pub enum InvalidStructureError {
+    /// The node refers to a node that does not exist.
+    MissingNode { index: usize, node_index: usize },
+    /// The node is under more than one node.
+    SharedNode { index: usize },
+    /// The anchor refers to a node that is not in the document.
+    UnreachableAnchor { index: usize, name: String },
+    /// The file does not refer to the file it is written in as its parent.
+    InvalidParent { index: usize },
+    /// The anchor refers to itself through the nodes it holds.
+    AnchorCycle { index: usize, name: String },
+}
+
+impl Display for InvalidStructureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidStructureError::MissingNode { index, node_index } => write!(
+                f,
+                "The node {} refers to the node {} that does not exist.",
+                index, node_index
+            ),
+            InvalidStructureError::SharedNode { index } => {
+                write!(f, "The node {} is under more than one node.", index)
+            }
+            InvalidStructureError::UnreachableAnchor { index, name } => write!(
+                f,
+                "The anchor {:?} of the node {} refers to a node that is not in the document.",
+                name, index
+            ),
+            InvalidStructureError::InvalidParent { index } => write!(
+                f,
+                "The file {} does not refer to the file it is written in as its parent.",
+                index
+            ),
+            InvalidStructureError::AnchorCycle { index, name } => write!(
+                f,
+                "The anchor {:?} of the node {} refers to itself through the nodes it holds.",
+                name, index
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvalidStructureError {}
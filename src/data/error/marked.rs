@@ -3,12 +3,17 @@ use std::{
     fmt::{Debug, Display, Formatter},
 };
 
+use super::super::{make::error::marked::MakeError, mark::Mark, node_path::NodePath};
+
 pub use super::with_mark::WithMarkError;
 
 pub type AnotherTypeError = WithMarkError<super::AnotherTypeError>;
+pub type ExpansionLimitError = WithMarkError<super::ExpansionLimitError>;
 pub type FailedDeserializeError<E> = WithMarkError<super::FailedDeserializeError<E>>;
 pub type InvalidIndexError = WithMarkError<super::InvalidIndexError>;
 pub type InvalidKeyError = WithMarkError<super::InvalidKeyError>;
+pub type TooLargeError = WithMarkError<super::TooLargeError>;
+pub type UnknownKeyError = WithMarkError<super::UnknownKeyError>;
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum ListError {
@@ -46,23 +51,173 @@ impl From<InvalidKeyError> for MapError {
     }
 }
 
+#[derive(PartialEq, Eq, Debug)]
+pub enum EditError<E: Error + PartialEq + Eq> {
+    ViewAnotherType(AnotherTypeError),
+    InvalidIndex(InvalidIndexError),
+    Make(MakeError<E>),
+}
+
+impl<E: Error + PartialEq + Eq> Display for EditError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditError::ViewAnotherType(e) => write!(f, "{}", e),
+            EditError::InvalidIndex(e) => write!(f, "{}", e),
+            EditError::Make(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Error + PartialEq + Eq> Error for EditError<E> {}
+
+impl<E: Error + PartialEq + Eq> From<AnotherTypeError> for EditError<E> {
+    fn from(value: AnotherTypeError) -> Self {
+        EditError::ViewAnotherType(value)
+    }
+}
+
+impl<E: Error + PartialEq + Eq> From<InvalidIndexError> for EditError<E> {
+    fn from(value: InvalidIndexError) -> Self {
+        EditError::InvalidIndex(value)
+    }
+}
+
+impl<E: Error + PartialEq + Eq> From<MakeError<E>> for EditError<E> {
+    fn from(value: MakeError<E>) -> Self {
+        EditError::Make(value)
+    }
+}
+
+/// Error of following a path through the document.
+///
+/// The path of a variant other than `InvalidPath` leads to the segment that could not be followed,
+/// inclusive.
+#[derive(PartialEq, Eq, Debug)]
+pub enum PathError {
+    InvalidPath(super::InvalidPathError),
+    ViewAnotherType(NodePath, AnotherTypeError),
+    InvalidIndex(NodePath, InvalidIndexError),
+    InvalidKey(NodePath, InvalidKeyError),
+}
+
+impl PathError {
+    /// Gets the path to the segment that could not be followed.
+    pub fn path(&self) -> Option<&NodePath> {
+        match self {
+            PathError::InvalidPath(_) => None,
+            PathError::ViewAnotherType(path, _)
+            | PathError::InvalidIndex(path, _)
+            | PathError::InvalidKey(path, _) => Some(path),
+        }
+    }
+
+    pub fn mark(&self) -> Option<Mark> {
+        match self {
+            PathError::InvalidPath(_) => None,
+            PathError::ViewAnotherType(_, e) => Some(e.mark),
+            PathError::InvalidIndex(_, e) => Some(e.mark),
+            PathError::InvalidKey(_, e) => Some(e.mark),
+        }
+    }
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathError::InvalidPath(e) => write!(f, "{}", e),
+            PathError::ViewAnotherType(path, e) => {
+                write!(f, "Failed to follow the path '{}'. {}", path, e)
+            }
+            PathError::InvalidIndex(path, e) => {
+                write!(f, "Failed to follow the path '{}'. {}", path, e)
+            }
+            PathError::InvalidKey(path, e) => {
+                write!(f, "Failed to follow the path '{}'. {}", path, e)
+            }
+        }
+    }
+}
+
+impl Error for PathError {}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum GetAsError<E: Error + PartialEq + Eq> {
+    Path(PathError),
+    FailedDecode(FailedDeserializeError<E>),
+}
+
+impl<E: Error + PartialEq + Eq> Display for GetAsError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GetAsError::Path(e) => write!(f, "{}", e),
+            GetAsError::FailedDecode(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Error + PartialEq + Eq> Error for GetAsError<E> {}
+
+impl<E: Error + PartialEq + Eq> From<PathError> for GetAsError<E> {
+    fn from(value: PathError) -> Self {
+        GetAsError::Path(value)
+    }
+}
+
+impl<E: Error + PartialEq + Eq> From<FailedDeserializeError<E>> for GetAsError<E> {
+    fn from(value: FailedDeserializeError<E>) -> Self {
+        GetAsError::FailedDecode(value)
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub enum DeserializeError<E: Error + PartialEq + Eq> {
     ViewAnotherType(AnotherTypeError),
     InvalidIndex(InvalidIndexError),
     InvalidKey(InvalidKeyError),
+    UnknownKey(UnknownKeyError),
     FailedDecode(FailedDeserializeError<E>),
+    ExpansionLimit(ExpansionLimitError),
+    Multiple(Vec<DeserializeError<E>>),
     Other(E),
     Failed,
 }
 
+impl<E: Error + PartialEq + Eq> DeserializeError<E> {
+    /// Splits the error into errors each of which has a single failure at the end of the chain of reasons.
+    pub fn split(self) -> Vec<Self> {
+        match self {
+            DeserializeError::FailedDecode(i) => {
+                let mark = i.mark;
+                i.data
+                    .split()
+                    .into_iter()
+                    .map(|data| DeserializeError::FailedDecode(WithMarkError::new(mark, data)))
+                    .collect()
+            }
+            DeserializeError::Multiple(i) => i.into_iter().flat_map(Self::split).collect(),
+            i => vec![i],
+        }
+    }
+}
+
 impl<E: Error + PartialEq + Eq> Display for DeserializeError<E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             DeserializeError::ViewAnotherType(e) => write!(f, "{}", e),
             DeserializeError::InvalidIndex(e) => write!(f, "{}", e),
             DeserializeError::InvalidKey(e) => write!(f, "{}", e),
+            DeserializeError::UnknownKey(e) => write!(f, "{}", e),
             DeserializeError::FailedDecode(e) => write!(f, "{}", e),
+            DeserializeError::ExpansionLimit(e) => write!(f, "{}", e),
+            DeserializeError::Multiple(e) => {
+                for (i, e) in e.iter().enumerate() {
+                    if i != 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", e)?;
+                }
+                Ok(())
+            }
             DeserializeError::Other(e) => write!(f, "{}", e),
             DeserializeError::Failed => write!(f, ""),
         }
@@ -87,6 +242,18 @@ impl<E: Error + PartialEq + Eq> From<InvalidKeyError> for DeserializeError<E> {
     }
 }
 
+impl<E: Error + PartialEq + Eq> From<UnknownKeyError> for DeserializeError<E> {
+    fn from(value: UnknownKeyError) -> Self {
+        DeserializeError::UnknownKey(value)
+    }
+}
+
+impl<E: Error + PartialEq + Eq> From<ExpansionLimitError> for DeserializeError<E> {
+    fn from(value: ExpansionLimitError) -> Self {
+        DeserializeError::ExpansionLimit(value)
+    }
+}
+
 impl<E: Error + PartialEq + Eq> From<FailedDeserializeError<E>> for DeserializeError<E> {
     fn from(value: FailedDeserializeError<E>) -> Self {
         DeserializeError::FailedDecode(value)
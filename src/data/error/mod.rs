@@ -1,11 +1,23 @@
 pub mod another_type;
+pub mod expansion_limit;
 pub mod failed_deserialize;
 pub mod invalid_index;
 pub mod invalid_key;
+pub mod invalid_path;
+pub mod invalid_selector;
+pub mod invalid_structure;
 pub mod marked;
+pub mod too_large;
+pub mod unknown_key;
 pub mod with_mark;
 
 pub use another_type::AnotherTypeError;
+pub use expansion_limit::{ExpansionLimit, ExpansionLimitError};
 pub use failed_deserialize::FailedDeserializeError;
 pub use invalid_index::InvalidIndexError;
 pub use invalid_key::InvalidKeyError;
+pub use invalid_path::InvalidPathError;
+pub use invalid_selector::InvalidSelectorError;
+pub use invalid_structure::InvalidStructureError;
+pub use too_large::TooLargeError;
+pub use unknown_key::UnknownKeyError;
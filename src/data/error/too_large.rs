@@ -0,0 +1,28 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct TooLargeError {
+    max_nodes: usize,
+}
+
+impl TooLargeError {
+    pub fn new(max_nodes: usize) -> Self {
+        Self { max_nodes }
+    }
+
+    pub fn get_max_nodes(&self) -> usize {
+        self.max_nodes
+    }
+}
+
+impl Display for TooLargeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The document with the anchors expanded has more than {} nodes.",
+            self.max_nodes
+        )
+    }
+}
+
+impl std::error::Error for TooLargeError {}
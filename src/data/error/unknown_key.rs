@@ -0,0 +1,33 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct UnknownKeyError {
+    key: String,
+    closest_key: Option<String>,
+}
+
+impl UnknownKeyError {
+    pub fn new(key: String, closest_key: Option<String>) -> Self {
+        Self { key, closest_key }
+    }
+
+    pub fn get_key(&self) -> &String {
+        &self.key
+    }
+
+    pub fn get_closest_key(&self) -> Option<&String> {
+        self.closest_key.as_ref()
+    }
+}
+
+impl Display for UnknownKeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "A key named '{}' is not expected in the map.", self.key)?;
+        match &self.closest_key {
+            Some(i) => write!(f, " Perhaps you meant '{}'.", i),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for UnknownKeyError {}
@@ -0,0 +1,195 @@
+use super::{data::Data, error::ExpansionLimit, node::node::Node, view::options::ExpansionLimits};
+
+/// Measures of a node with the anchors expanded, saturated at `usize::MAX`.
+///
+/// The limits bound only what the references add, so `nodes` and `depth` count the nodes reached
+/// through a reference, the nodes written in the document itself are free.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub(crate) struct Expansion {
+    /// Nodes reached through references, a reference counts as a node.
+    pub(crate) nodes: usize,
+    pub(crate) dereferences: usize,
+    /// The most levels of nodes reached through a reference, the reference counts as a level.
+    pub(crate) depth: usize,
+    /// All the nodes of the node with the anchors expanded.
+    total_nodes: usize,
+    /// All the levels of the node with the anchors expanded.
+    total_depth: usize,
+}
+
+impl Expansion {
+    /// Measures of a reference leading back to itself, which expands without end.
+    const ENDLESS: Self = Self {
+        nodes: usize::MAX,
+        dereferences: usize::MAX,
+        depth: usize::MAX,
+        total_nodes: usize::MAX,
+        total_depth: usize::MAX,
+    };
+
+    /// Gets the first of the limits the expansion goes over, with its bound.
+    pub(crate) fn exceeded(&self, limits: &ExpansionLimits) -> Option<(ExpansionLimit, usize)> {
+        let check = |value: usize, max: Option<usize>, limit| {
+            max.filter(|max| value > *max).map(|max| (limit, max))
+        };
+        check(self.nodes, limits.max_nodes, ExpansionLimit::Nodes)
+            .or_else(|| {
+                let max = limits.max_dereferences;
+                check(self.dereferences, max, ExpansionLimit::Dereferences)
+            })
+            .or_else(|| check(self.depth, limits.max_depth, ExpansionLimit::Depth))
+    }
+}
+
+/// Gets the nodes the node expands to and the number of references followed to reach them.
+fn targets(data: &Data, index: usize) -> (Vec<usize>, usize) {
+    match &data.get(index).node {
+        Node::GetAnchor(i) => (vec![i.node_index], 1),
+        _ => (data.children(index), 0),
+    }
+}
+
+/// Measures every node of the document with the anchors expanded.
+///
+/// Goes through the nodes with a stack of its own, so a deeply nested document does not overflow
+/// the call stack.
+pub(crate) fn expansions(data: &Data) -> Vec<Expansion> {
+    let len = data.data.len();
+    let mut result = vec![None; len];
+    let mut in_progress = vec![false; len];
+    // Nodes to measure, `true` once the nodes they expand to are measured.
+    let mut stack = (0..len).rev().map(|i| (i, false)).collect::<Vec<_>>();
+    while let Some((index, ready)) = stack.pop() {
+        if result[index].is_some() || (!ready && in_progress[index]) {
+            continue;
+        }
+        let (nodes, dereferences) = targets(data, index);
+        if !ready {
+            in_progress[index] = true;
+            stack.push((index, true));
+            stack.extend(nodes.into_iter().rev().map(|i| (i, false)));
+            continue;
+        }
+        let mut value = Expansion {
+            dereferences,
+            total_nodes: 1,
+            ..Default::default()
+        };
+        for i in nodes {
+            // A node not measured by now is the one being measured, the reference leads back to it.
+            let i = result[i].unwrap_or(Expansion::ENDLESS);
+            value.nodes = value.nodes.saturating_add(i.nodes);
+            value.dereferences = value.dereferences.saturating_add(i.dereferences);
+            value.depth = value.depth.max(i.depth);
+            value.total_nodes = value.total_nodes.saturating_add(i.total_nodes);
+            value.total_depth = value.total_depth.max(i.total_depth);
+        }
+        value.total_depth = value.total_depth.saturating_add(1);
+        // Everything under a reference is reached through it.
+        if dereferences != 0 {
+            value.nodes = value.total_nodes;
+            value.depth = value.total_depth;
+        }
+        in_progress[index] = false;
+        result[index] = Some(value);
+    }
+    result.into_iter().map(Option::unwrap_or_default).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        error::{marked, ExpansionLimitError},
+        mark::Mark,
+        node::node::{GetAnchorNode, ListNode, MarkedNode},
+        node_id::NodeId,
+        value::Value,
+        view::options::{ExpansionLimits, ViewOptions},
+    };
+    use super::*;
+    use crate::cst::document::Cst;
+    use std::convert::Infallible;
+
+    #[test]
+    fn test_expansions() {
+        let source = "a: &a
+	- 1
+	- 1
+b: &b
+	- *a
+	- *a
+c:
+	- *b
+	- *b
+";
+        let data = Cst::parse("test.ieml", source).unwrap().to_data().unwrap();
+        let NodeId(c) = data.view().at("c").unwrap().id();
+        let expected = Expansion {
+            nodes: 20,
+            dereferences: 6,
+            depth: 5,
+            total_nodes: 21,
+            total_depth: 6,
+        };
+        assert_eq!(expansions(&data)[c], expected);
+
+        let node = |node| MarkedNode::new(node, Mark::default());
+        let data = Data::new([
+            node(Node::List(ListNode::new(vec![1]))),
+            node(Node::GetAnchor(GetAnchorNode::new("a".into(), 0))),
+            node(Node::List(ListNode::new(vec![0]))),
+        ]);
+        assert_eq!(expansions(&data)[1], Expansion::ENDLESS);
+    }
+
+    #[test]
+    fn test_expansions_deep() {
+        let depth = 100_000;
+        let mut data = Data::default();
+        data.data.push(MarkedNode::new(Node::Null, Mark::default()));
+        for i in 0..depth {
+            let node = Node::List(ListNode::new(vec![i]));
+            data.data.push(MarkedNode::new(node, Mark::default()));
+        }
+        let expansion = expansions(&data)[depth];
+        assert_eq!(expansion.total_depth, depth + 1);
+        assert_eq!(expansion.depth, 0);
+    }
+
+    #[test]
+    fn test_default_limits_without_anchors() {
+        let mut data = Data::default();
+        let len = ExpansionLimits::DEFAULT_MAX_NODES + 1;
+        for _ in 0..len {
+            data.data.push(MarkedNode::new(Node::Null, Mark::default()));
+        }
+        let wide = Node::List(ListNode::new((0..len).collect()));
+        data.data.push(MarkedNode::new(wide, Mark::default()));
+        let value = data.view().decode::<Infallible, Value>().unwrap();
+        assert_eq!(value.as_list().map(Vec::len), Some(len));
+
+        for i in 0..ExpansionLimits::DEFAULT_MAX_DEPTH {
+            let node = Node::List(ListNode::new(vec![len + i]));
+            data.push(MarkedNode::new(node, Mark::default()));
+        }
+        assert_eq!(data.view().check_expansion(), Ok(()));
+    }
+
+    #[test]
+    fn test_default_limits() {
+        let mut source = "a0: &a0 null\n".to_string();
+        for i in 1..=24 {
+            source += &format!("a{i}: &a{i}\n\t- *a{}\n\t- *a{}\n", i - 1, i - 1);
+        }
+        let data = Cst::parse("test.ieml", &source).unwrap().to_data().unwrap();
+        let view = data.view();
+        let options = ViewOptions::default().with_limits(ExpansionLimits::unlimited());
+        assert_eq!(view.clone().with_options(options).check_expansion(), Ok(()));
+
+        let error = marked::ExpansionLimitError::new(
+            Mark::new(56, 1),
+            ExpansionLimitError::new(ExpansionLimit::Nodes, ExpansionLimits::DEFAULT_MAX_NODES),
+        );
+        assert_eq!(view.check_expansion(), Err(error));
+    }
+}
@@ -1,15 +1,19 @@
 use super::{
     super::{
+        comments::Comments,
         data::Data,
         mark::Mark,
         node::node::{
-            FileNode, GetAnchorNode, ListNode, MapNode, Node, TaggedNode, TakeAnchorNode,
+            FileNode, GetAnchorNode, ListNode, MapNode, Node, StringNode, TaggedNode,
+            TakeAnchorNode,
         },
+        string_style::StringStyle,
     },
     error::*,
     init::init,
+    options::{DuplicateKeys, MakeOptions},
 };
-use std::{error::Error, path::PathBuf};
+use std::{collections::HashMap, error::Error, path::PathBuf};
 
 pub use super::maker::Maker;
 
@@ -46,12 +50,25 @@ pub fn string<O, E, S>(
     output: O,
     string: S,
 ) -> impl FnOnce(&mut Maker) -> marked::MakeResult<O, E>
+where
+    E: Error + PartialEq + Eq,
+    S: Into<String>,
+{
+    string_with_style(begin_mark, output, string, Default::default())
+}
+
+pub fn string_with_style<O, E, S>(
+    begin_mark: Mark,
+    output: O,
+    string: S,
+    style: StringStyle,
+) -> impl FnOnce(&mut Maker) -> marked::MakeResult<O, E>
 where
     E: Error + PartialEq + Eq,
     S: Into<String>,
 {
     move |maker| {
-        maker.add(begin_mark, Node::String(string.into()));
+        maker.add(begin_mark, Node::String(StringNode::new(string.into(), style)));
         Ok(output)
     }
 }
@@ -96,18 +113,35 @@ where
 {
     move |maker| {
         let mut output = output;
-        let result: Result<_, _> = iter
-            .map(|(key, f)| {
-                f(maker).map(|mark| {
-                    output = mark;
-                    (key.into(), maker.last())
-                })
-            })
-            .collect();
-        result.map(|i| {
-            maker.add(begin_mark, Node::Map(MapNode::new(i)));
-            output
-        })
+        let mut result = MapNode::default();
+        // Nodes made for each value, the anchors taken in them are forgotten if the value is dropped.
+        let mut values = HashMap::new();
+        for (key, f) in iter {
+            let begin = maker.len();
+            output = f(maker)?;
+            let (key, index) = (key.into(), maker.last());
+            let Some(first) = result.get(&key) else {
+                values.insert(key.clone(), begin..=index);
+                result.insert(key, index);
+                continue;
+            };
+            let dropped = match maker.options().duplicate_keys {
+                DuplicateKeys::Deny => {
+                    let (first, second) = (maker.mark(first), maker.mark(index));
+                    let reason = MakeErrorReason::DuplicateKey { key, first, second };
+                    return Err(marked::MakeError::new_with(second, maker.path(), reason));
+                }
+                DuplicateKeys::LastWins => {
+                    result.insert(key.clone(), index);
+                    let first = values.insert(key, begin..=index);
+                    first.expect("The nodes of the first value were not recorded.")
+                }
+                DuplicateKeys::FirstWins => begin..=index,
+            };
+            maker.forget_anchors(dropped);
+        }
+        maker.add(begin_mark, Node::Map(result));
+        Ok(output)
     }
 }
 
@@ -153,7 +187,7 @@ where
                     (key.into(), maker.last())
                 })
             })
-            .collect::<Result<_, _>>()?;
+            .collect::<Result<Vec<_>, _>>()?;
         let result = maker.child(|maker| {
             f(maker).map(|_| {
                 FileNode::new(
@@ -215,13 +249,56 @@ where
     }
 }
 
+/// Attaches the comments to the node made by `f`.
+pub fn comments<O, E, F>(
+    comments: Comments,
+    f: F,
+) -> impl FnOnce(&mut Maker) -> marked::MakeResult<O, E>
+where
+    E: Error + PartialEq + Eq,
+    F: FnOnce(&mut Maker) -> marked::MakeResult<O, E>,
+{
+    move |maker| {
+        let result = f(maker)?;
+        if !comments.is_empty() {
+            maker.add_comments(maker.last(), comments);
+        }
+        Ok(result)
+    }
+}
+
+/// Sets the mark at which the node made by `f` ends in the source.
+pub fn end<O, E, F>(end_mark: Mark, f: F) -> impl FnOnce(&mut Maker) -> marked::MakeResult<O, E>
+where
+    E: Error + PartialEq + Eq,
+    F: FnOnce(&mut Maker) -> marked::MakeResult<O, E>,
+{
+    move |maker| {
+        let result = f(maker)?;
+        maker.set_end(maker.last(), end_mark);
+        Ok(result)
+    }
+}
+
 pub fn make<O, E, F>(begin_mark: Mark, f: F) -> Result<Data, marked::MakeError<E>>
+where
+    E: Error + PartialEq + Eq,
+    F: FnOnce(&mut Maker) -> marked::MakeResult<O, E>,
+{
+    make_with_options(begin_mark, Default::default(), f)
+}
+
+pub fn make_with_options<O, E, F>(
+    begin_mark: Mark,
+    options: MakeOptions,
+    f: F,
+) -> Result<Data, marked::MakeError<E>>
 where
     E: Error + PartialEq + Eq,
     F: FnOnce(&mut Maker) -> marked::MakeResult<O, E>,
 {
     let mut data = Data::default();
-    let mut maker = Maker::new(&mut data, PathBuf::new());
+    let mut maker = Maker::with_options(&mut data, PathBuf::new(), options);
     let result = maker.child(|maker| {
         f(maker).map(|_| FileNode {
             node_index: maker.last(),
@@ -241,6 +318,25 @@ pub fn make_file<O, E, F, A, S, I>(
     anchors: I,
     f: F,
 ) -> Result<Data, marked::MakeError<E>>
+where
+    E: Error + PartialEq + Eq,
+    F: FnOnce(&mut Maker) -> marked::MakeResult<O, E>,
+    A: FnOnce(&mut Maker) -> marked::MakeResult<O, E>,
+    S: Into<String>,
+    I: Iterator<Item = (S, A)>,
+{
+    let options = Default::default();
+    make_file_with_options(begin_mark, output, path, options, anchors, f)
+}
+
+pub fn make_file_with_options<O, E, F, A, S, I>(
+    begin_mark: Mark,
+    output: O,
+    path: PathBuf,
+    options: MakeOptions,
+    anchors: I,
+    f: F,
+) -> Result<Data, marked::MakeError<E>>
 where
     E: Error + PartialEq + Eq,
     F: FnOnce(&mut Maker) -> marked::MakeResult<O, E>,
@@ -249,7 +345,7 @@ where
     I: Iterator<Item = (S, A)>,
 {
     let mut data = Data::default();
-    let mut maker = Maker::new(&mut data, path.clone());
+    let mut maker = Maker::with_options(&mut data, path.clone(), options);
     file(begin_mark, output, path, anchors, f)(&mut maker)?;
     init(&mut data)?;
     Ok(data)
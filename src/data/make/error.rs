@@ -1,3 +1,4 @@
+use super::super::mark::Mark;
 use std::fmt::{Display, Formatter};
 use std::{error::Error, path::PathBuf};
 
@@ -5,6 +6,22 @@ use std::{error::Error, path::PathBuf};
 pub enum MakeErrorReason<E: Error + PartialEq + Eq> {
     AnchorAlreadyExist(String),
     AnchorDoesntExist(String),
+    /// An anchor was to be removed while a `GetAnchor` outside the removed nodes refers to it.
+    AnchorInUse(String),
+    /// The key occurs in the map more than once, the marks are of the values of both occurrences.
+    DuplicateKey {
+        key: String,
+        first: Mark,
+        second: Mark,
+    },
+    /// The anchors refer to each other in a cycle, the names go from the anchor the cycle was
+    /// found at back to it.
+    AnchorCycle(Vec<String>),
+    /// An anchor was taken in a document whose root is not a `File`, so there is no place to
+    /// keep it.
+    AnchorWithoutFile(String),
+    /// The file includes itself, the paths go from the file the cycle was found at back to it.
+    IncludeCycle(Vec<PathBuf>),
     Parse(E),
 }
 
@@ -13,6 +30,11 @@ impl<E: Error + PartialEq + Eq> Display for MakeErrorReason<E> {
         match self {
             MakeErrorReason::AnchorAlreadyExist(i) => write!(f, "An attempt was made to take an anchor with the name of an anchor that already exists. Anchor name: {:?}.", i),
             MakeErrorReason::AnchorDoesntExist(i) => write!(f, "There is no requested anchor. Anchor name: {:?}.", i),
+            MakeErrorReason::AnchorInUse(i) => write!(f, "An attempt was made to remove an anchor that is still referenced. Anchor name: {:?}.", i),
+            MakeErrorReason::DuplicateKey { key, first, second } => write!(f, "The key occurs in the map more than once, at {}:{} and at {}:{}. Key: {:?}.", first.line, first.symbol, second.line, second.symbol, key),
+            MakeErrorReason::AnchorCycle(i) => write!(f, "The anchors refer to each other in a cycle. Anchors: {}.", i.join(" -> ")),
+            MakeErrorReason::AnchorWithoutFile(i) => write!(f, "An attempt was made to take an anchor in a document without a root file. Anchor name: {:?}.", i),
+            MakeErrorReason::IncludeCycle(i) => write!(f, "The file includes itself. Files: {}.", i.iter().map(|i| format!("{:?}", i)).collect::<Vec<_>>().join(" -> ")),
             MakeErrorReason::Parse(i) => write!(f, "{i}"),
         }
     }
@@ -1,6 +1,6 @@
 use super::super::{data::Data, node::node::Node, view::anchors::Anchors};
 use super::error::*;
-use std::error::Error;
+use std::{collections::HashMap, error::Error, path::Path};
 
 pub(crate) fn init_step<E: Error + PartialEq + Eq>(
     data: &mut Data,
@@ -8,58 +8,168 @@ pub(crate) fn init_step<E: Error + PartialEq + Eq>(
     index: usize,
 ) -> Result<(), marked::MakeError<E>> {
     let mut node = std::mem::take(data.get_mut(index));
-    match &mut node.node {
-        Node::List(i) => {
-            for i in i.data.iter() {
-                init_step(data, file_index, *i)?;
-            }
-        }
-        Node::Map(i) => {
-            for (_, i) in i.data.iter() {
-                init_step(data, file_index, *i)?;
-            }
+    let result = match &mut node.node {
+        Node::List(i) => i
+            .data
+            .iter()
+            .try_for_each(|i| init_step(data, file_index, *i)),
+        Node::Map(i) => i
+            .entries()
+            .try_for_each(|(_, i)| init_step(data, file_index, i)),
+        Node::Tagged(i) => init_step(data, file_index, i.node_index),
+        Node::File(i) => {
+            i.parent = Some(file_index);
+            Ok(())
         }
-        Node::Tagged(i) => init_step(data, file_index, i.node_index)?,
-        Node::File(i) => i.parent = Some(file_index),
-        Node::TakeAnchor(i) => init_step(data, file_index, i.node_index)?,
+        Node::TakeAnchor(i) => init_step(data, file_index, i.node_index),
         Node::GetAnchor(i) => {
             let file_node = std::mem::take(data.get_mut(file_index));
-            match &file_node.node {
+            let result = match &file_node.node {
                 Node::File(file) => {
-                    let anchors = Anchors::new(Default::default(), file, data, ());
+                    let anchors =
+                        Anchors::new(Default::default(), file, data, (), Default::default());
                     match anchors.get_index(i.name.as_str()) {
-                        Some(j) => i.node_index = j,
-                        None => {
-                            return Err(marked::MakeError::new(
-                                node.mark,
-                                MakeError::new(
-                                    file.path.clone(),
-                                    MakeErrorReason::AnchorDoesntExist(i.name.clone()),
-                                ),
-                            ))
+                        Some(j) => {
+                            i.node_index = j;
+                            Ok(())
                         }
-                    };
+                        None => Err(marked::MakeError::new(
+                            node.mark,
+                            MakeError::new(
+                                file.path.clone(),
+                                MakeErrorReason::AnchorDoesntExist(i.name.clone()),
+                            ),
+                        )),
+                    }
                 }
                 _ => panic!("Incorrect document structure, the node is not a File."),
-            }
+            };
             *data.get_mut(file_index) = file_node;
+            result
         }
-        _ => {}
-    }
+        _ => Ok(()),
+    };
     *data.get_mut(index) = node;
+    result?;
     if let Node::File(ref i) = data.get(index).node {
-        let file_anchors = i.file_anchors.data.values().copied().collect::<Vec<_>>();
+        let file_anchors = i.file_anchors.entries().map(|(_, i)| i).collect::<Vec<_>>();
         init_step(data, index, i.node_index)?;
+        // The anchors passed to the file are written in the file it is included from.
         for i in file_anchors {
-            init_step(data, index, i)?;
+            init_step(data, file_index, i)?;
+        }
+    }
+    Ok(())
+}
+
+/// Gets the `GetAnchor` nodes under the node, including the node itself, without going into the
+/// nodes they refer to.
+fn get_anchors_under(data: &Data, index: usize) -> Vec<usize> {
+    let mut result = Vec::new();
+    let mut stack = vec![index];
+    while let Some(index) = stack.pop() {
+        if let Node::GetAnchor(_) = data.get(index).node {
+            result.push(index);
+        }
+        stack.extend(data.children(index));
+    }
+    result
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+/// Follows the references from the `GetAnchor`, returns the `GetAnchor` nodes of a cycle if the
+/// references lead back to one of the nodes in the chain.
+fn find_cycle(
+    data: &Data,
+    index: usize,
+    visits: &mut HashMap<usize, Visit>,
+    chain: &mut Vec<usize>,
+) -> Option<Vec<usize>> {
+    match visits.get(&index) {
+        Some(Visit::Done) => return None,
+        Some(Visit::InProgress) => {
+            let begin = chain.iter().position(|i| *i == index).unwrap_or_default();
+            let mut cycle = chain[begin..].to_vec();
+            cycle.push(index);
+            return Some(cycle);
+        }
+        None => {}
+    }
+    let Node::GetAnchor(node) = &data.get(index).node else {
+        return None;
+    };
+    visits.insert(index, Visit::InProgress);
+    chain.push(index);
+    for i in get_anchors_under(data, node.node_index) {
+        if let Some(cycle) = find_cycle(data, i, visits, chain) {
+            return Some(cycle);
+        }
+    }
+    chain.pop();
+    visits.insert(index, Visit::Done);
+    None
+}
+
+/// Finds a cycle of references starting at one of the `GetAnchor` nodes, returns the `GetAnchor`
+/// nodes of the cycle from the first one back to it.
+pub(crate) fn find_anchor_cycle<I: IntoIterator<Item = usize>>(
+    data: &Data,
+    indexes: I,
+) -> Option<Vec<usize>> {
+    let mut visits = HashMap::new();
+    indexes
+        .into_iter()
+        .find_map(|i| find_cycle(data, i, &mut visits, &mut Vec::new()))
+}
+
+/// Checks that no anchor under the root refers to itself through the nodes it holds, so following
+/// the references always ends.
+pub(crate) fn check_cycles<E: Error + PartialEq + Eq>(
+    data: &Data,
+    root: usize,
+) -> Result<(), marked::MakeError<E>> {
+    // Paths of the files the `GetAnchor` nodes are written in.
+    let mut files = HashMap::<usize, &Path>::new();
+    let mut stack = vec![(root, Path::new(""))];
+    while let Some((index, file)) = stack.pop() {
+        match &data.get(index).node {
+            Node::File(i) => {
+                stack.extend(i.file_anchors.entries().map(|(_, i)| (i, file)));
+                stack.push((i.node_index, i.path.as_path()));
+            }
+            Node::GetAnchor(_) => {
+                files.insert(index, file);
+            }
+            _ => stack.extend(data.children(index).into_iter().map(|i| (i, file))),
         }
     }
+
+    let mut indexes = files.keys().copied().collect::<Vec<_>>();
+    indexes.sort_unstable();
+    if let Some(cycle) = find_anchor_cycle(data, indexes) {
+        let name = |i: &usize| match &data.get(*i).node {
+            Node::GetAnchor(node) => node.name.clone(),
+            _ => panic!("Incorrect document structure, the node is not a GetAnchor."),
+        };
+        let last = cycle[cycle.len() - 2];
+        let file = files.get(&last).map(|i| i.to_path_buf());
+        return Err(marked::MakeError::new_with(
+            data.get(last).mark,
+            file.unwrap_or_default(),
+            MakeErrorReason::AnchorCycle(cycle.iter().map(name).collect()),
+        ));
+    }
     Ok(())
 }
 
 pub(crate) fn init<E: Error + PartialEq + Eq>(data: &mut Data) -> Result<(), marked::MakeError<E>> {
-    match &data.get(data.data.len() - 1).node {
-        Node::File(i) => init_step(data, data.data.len() - 1, i.node_index),
-        _ => Ok(()),
+    if let Node::File(i) = &data.get(data.data.len() - 1).node {
+        init_step(data, data.data.len() - 1, i.node_index)?;
     }
+    check_cycles(data, data.root())
 }
@@ -1,22 +1,33 @@
 use super::super::{
+    comments::Comments,
     data::Data,
     mark::Mark,
     node::node::{MapNode, MarkedNode, Node},
 };
-use std::path::{Path, PathBuf};
+use super::options::MakeOptions;
+use std::{
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
 
 pub struct Maker<'a> {
     data: &'a mut Data,
     anchors: MapNode,
     path: PathBuf,
+    options: MakeOptions,
 }
 
 impl<'a> Maker<'a> {
-    pub(super) fn new(data: &'a mut Data, path: PathBuf) -> Self {
+    pub(crate) fn new(data: &'a mut Data, path: PathBuf) -> Self {
+        Self::with_options(data, path, Default::default())
+    }
+
+    pub(crate) fn with_options(data: &'a mut Data, path: PathBuf, options: MakeOptions) -> Self {
         Self {
             data,
             anchors: Default::default(),
             path,
+            options,
         }
     }
 
@@ -28,28 +39,49 @@ impl<'a> Maker<'a> {
     }
 
     pub(super) fn add(&mut self, mark: Mark, node: Node) {
-        self.data
-            .data
-            .insert(self.data.data.len(), MarkedNode::new(node, mark));
+        self.data.push(MarkedNode::new(node, mark));
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.data.data.len()
     }
 
-    pub(super) fn last(&self) -> usize {
+    pub(crate) fn last(&self) -> usize {
         self.data.data.len() - 1
     }
 
+    pub(super) fn mark(&self, index: usize) -> Mark {
+        self.data.get(index).mark
+    }
+
     pub(super) fn add_anchor(&mut self, name: String, index: usize) -> Option<()> {
-        self.anchors
-            .data
-            .insert(name, index)
-            .is_none()
-            .then_some(())
+        self.anchors.insert(name, index).is_none().then_some(())
     }
 
-    pub(super) fn anchors(&mut self) -> &mut MapNode {
+    /// Forgets the anchors taken in the nodes and the comments attached to them, used when the
+    /// nodes are dropped from the document.
+    pub(super) fn forget_anchors(&mut self, nodes: RangeInclusive<usize>) {
+        self.anchors.retain(|_, i| !nodes.contains(&i));
+        self.data.comments.retain(|i, _| !nodes.contains(i));
+    }
+
+    pub(super) fn set_end(&mut self, index: usize, end: Mark) {
+        self.data.get_mut(index).end = Some(end);
+    }
+
+    pub(super) fn add_comments(&mut self, index: usize, comments: Comments) {
+        self.data.comments.insert(index, comments);
+    }
+
+    pub(crate) fn anchors(&mut self) -> &mut MapNode {
         &mut self.anchors
     }
 
     pub fn path(&self) -> &Path {
         self.path.as_path()
     }
+
+    pub fn options(&self) -> MakeOptions {
+        self.options
+    }
 }
@@ -1,6 +1,7 @@
 pub mod combinator;
 pub mod error;
-mod init;
+pub(crate) mod init;
 pub mod maker;
+pub mod options;
 
 pub use combinator::*;
@@ -0,0 +1,22 @@
+/// What to do with a key that occurs in a map more than once.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DuplicateKeys {
+    #[default]
+    Deny,
+    /// Keeps the value of the last occurrence at the place of the first one.
+    LastWins,
+    /// Keeps the value of the first occurrence.
+    FirstWins,
+}
+
+/// Settings used while the document is being made.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct MakeOptions {
+    pub duplicate_keys: DuplicateKeys,
+}
+
+impl MakeOptions {
+    pub fn new(duplicate_keys: DuplicateKeys) -> Self {
+        Self { duplicate_keys }
+    }
+}
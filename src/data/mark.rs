@@ -25,3 +25,15 @@ impl std::ops::Add for Mark {
         }
     }
 }
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Span {
+    pub begin: Mark,
+    pub end: Mark,
+}
+
+impl Span {
+    pub fn new(begin: Mark, end: Mark) -> Self {
+        Self { begin, end }
+    }
+}
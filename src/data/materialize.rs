@@ -0,0 +1,225 @@
+use super::{
+    data::Data,
+    error::{marked, TooLargeError},
+    node::node::{FileNode, ListNode, MapNode, MarkedNode, Node, TaggedNode},
+};
+
+/// Settings of `Data::materialize`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MaterializeOptions {
+    /// Replaces every `File` with its contents, so the result reads as a single file.
+    pub flatten_files: bool,
+    /// The most nodes the result may have, guards against anchors referring to each other so
+    /// that the size doubles at each step.
+    pub max_nodes: usize,
+}
+
+impl MaterializeOptions {
+    pub const DEFAULT_MAX_NODES: usize = 1 << 20;
+
+    pub fn new(flatten_files: bool, max_nodes: usize) -> Self {
+        Self {
+            flatten_files,
+            max_nodes,
+        }
+    }
+}
+
+impl Default for MaterializeOptions {
+    fn default() -> Self {
+        Self::new(false, Self::DEFAULT_MAX_NODES)
+    }
+}
+
+pub(crate) struct Materializer<'data> {
+    source: &'data Data,
+    options: MaterializeOptions,
+    result: Data,
+    /// `GetAnchor` nodes being expanded, from the outermost one.
+    expanding: Vec<usize>,
+}
+
+impl<'data> Materializer<'data> {
+    pub(crate) fn new(source: &'data Data, options: MaterializeOptions) -> Self {
+        Self {
+            source,
+            options,
+            result: Data::default(),
+            expanding: Vec::new(),
+        }
+    }
+
+    /// Marks the error with the innermost reference being expanded, or with the node if there is
+    /// none.
+    fn error(&self, index: usize) -> marked::TooLargeError {
+        let index = self.expanding.last().copied().unwrap_or(index);
+        let error = TooLargeError::new(self.options.max_nodes);
+        marked::TooLargeError::new(self.source.get(index).mark, error)
+    }
+
+    /// Gives the copy the comments of the source node, the nodes copied for a reference have the
+    /// comments at the place they are taken.
+    fn copy_comments(&mut self, index: usize, new_index: usize) {
+        if !self.expanding.is_empty() {
+            return;
+        }
+        if let Some(comments) = self.source.comments.get(&index) {
+            let comments = comments.clone();
+            self.result.comments.entry(new_index).or_insert(comments);
+        }
+    }
+
+    fn add(&mut self, index: usize, node: Node) -> Result<usize, marked::TooLargeError> {
+        if self.result.data.len() >= self.options.max_nodes {
+            return Err(self.error(index));
+        }
+        let source = self.source.get(index);
+        let mut node = MarkedNode::new(node, source.mark);
+        node.end = source.end;
+        self.result.data.push(node);
+        let new_index = self.result.data.len() - 1;
+        self.copy_comments(index, new_index);
+        Ok(new_index)
+    }
+
+    /// Copies the node under a node that is left out, the copy takes the comments of both.
+    fn unwrap(&mut self, index: usize, node_index: usize) -> Result<usize, marked::TooLargeError> {
+        let new_index = self.node(node_index)?;
+        self.copy_comments(index, new_index);
+        Ok(new_index)
+    }
+
+    /// Copies the node with all the nodes under it, returns the index of the copy.
+    fn node(&mut self, index: usize) -> Result<usize, marked::TooLargeError> {
+        let source = self.source;
+        let node = match &source.get(index).node {
+            Node::Null => Node::Null,
+            Node::Raw(i) => Node::Raw(i.clone()),
+            Node::String(i) => Node::String(i.clone()),
+            Node::List(i) => {
+                let items = i.data.iter().map(|i| self.node(*i));
+                Node::List(ListNode::new(items.collect::<Result<_, _>>()?))
+            }
+            Node::Map(i) => {
+                let entries = i
+                    .entries()
+                    .map(|(key, i)| self.node(i).map(|i| (key.clone(), i)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Node::Map(MapNode::new(entries))
+            }
+            Node::Tagged(i) => {
+                Node::Tagged(TaggedNode::new(i.tag.clone(), self.node(i.node_index)?))
+            }
+            Node::File(i) if self.options.flatten_files => return self.unwrap(index, i.node_index),
+            Node::File(i) => {
+                let node_index = self.node(i.node_index)?;
+                let (anchors, file_anchors) = Default::default();
+                let path = i.path.clone();
+                Node::File(FileNode::new(path, node_index, anchors, file_anchors, None))
+            }
+            Node::TakeAnchor(i) => return self.unwrap(index, i.node_index),
+            Node::GetAnchor(i) => {
+                // Making and editing reject anchor cycles, so the expansion ends.
+                debug_assert!(
+                    !self.expanding.contains(&index),
+                    "Incorrect document structure, the anchors refer to each other in a cycle."
+                );
+                self.expanding.push(index);
+                let result = self.node(i.node_index);
+                self.expanding.pop();
+                let new_index = result?;
+                self.copy_comments(index, new_index);
+                return Ok(new_index);
+            }
+        };
+        self.add(index, node)
+    }
+
+    pub(crate) fn materialize(mut self) -> Result<Data, marked::TooLargeError> {
+        if self.source.data.is_empty() {
+            return Ok(self.result);
+        }
+        self.node(self.source.root())?;
+        let mut result = self.result;
+        let mut stack = vec![(result.root(), None)];
+        while let Some((index, file)) = stack.pop() {
+            let mut file = file;
+            if let Node::File(i) = &mut result.data[index].node {
+                i.parent = file;
+                file = Some(index);
+            }
+            stack.extend(result.children(index).into_iter().map(|i| (i, file)));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mark::Mark;
+    use super::*;
+    use crate::{cst::document::Cst, test_dir::TestDir};
+
+    #[test]
+    fn test_materialize() {
+        let dir = TestDir::new("materialize");
+        std::fs::write(dir.join("include.ieml"), "value: *name\nport: *port\n").unwrap();
+        let source = "# names
+# the name
+name: &name main
+copy: *name
+include: < include.ieml
+	port: &port 80
+";
+        let data = Cst::parse(dir.join("main.ieml"), source)
+            .unwrap()
+            .to_data()
+            .unwrap();
+
+        let result = data.materialize(Default::default()).unwrap();
+        assert_eq!(result.validate(), Ok(()));
+        assert!(result.anchor_usage().anchors().is_empty());
+        let expected = "# names
+# the name
+name: main
+copy: main
+include: < include.ieml
+";
+        assert_eq!(result.to_string(), expected);
+        let include = result.view().at("include").unwrap();
+        let include = include.file().unwrap().view();
+        assert_eq!(include.at("value").unwrap().raw().unwrap().raw(), "main");
+        assert_eq!(include.at("port").unwrap().raw().unwrap().raw(), "80");
+
+        let options = MaterializeOptions::new(true, MaterializeOptions::DEFAULT_MAX_NODES);
+        let result = data.materialize(options).unwrap();
+        assert_eq!(result.validate(), Ok(()));
+        assert!(result.view().is_map());
+        let port = result.view().at("include.port").unwrap();
+        assert_eq!(port.raw().unwrap().raw(), "80");
+    }
+
+    #[test]
+    fn test_materialize_limit() {
+        let source = "a: &a
+	- 1
+	- 1
+b: &b
+	- *a
+	- *a
+c:
+	- *b
+	- *b
+";
+        let data = Cst::parse("test.ieml", source).unwrap().to_data().unwrap();
+        let result = data.materialize(Default::default()).unwrap();
+        assert_eq!(result.data.len(), 27);
+        let options = MaterializeOptions::new(false, 27);
+        assert!(data.materialize(options).is_ok());
+
+        let options = MaterializeOptions::new(false, 20);
+        let error = data.materialize(options).unwrap_err();
+        assert_eq!(error.data, TooLargeError::new(20));
+        assert_eq!(error.mark, Mark::new(5, 3));
+    }
+}
@@ -1,7 +1,16 @@
+pub mod anchor_usage;
 pub mod node;
+pub mod comments;
 pub mod error;
+pub(crate) mod expansion;
 pub mod make;
 pub mod mark;
+pub mod materialize;
 pub mod view;
+pub mod node_id;
+pub mod node_path;
 pub mod node_type;
+pub mod spanned;
+pub mod string_style;
+pub mod value;
 pub mod data;
@@ -1,12 +1,93 @@
-use std::collections::HashMap;
+use std::{borrow::Borrow, collections::HashMap, hash::Hash, ops::Index};
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// Map of keys to node indexes that remembers the order in which the keys were added.
+#[derive(Debug, Clone, Eq, Default)]
 pub(crate) struct MapNode {
-    pub(crate) data: HashMap<String, usize>,
+    data: HashMap<String, usize>,
+    keys: Vec<String>,
 }
 
 impl MapNode {
-    pub(crate) fn new(data: HashMap<String, usize>) -> Self {
-        Self { data }
+    pub(crate) fn new<I: IntoIterator<Item = (String, usize)>>(data: I) -> Self {
+        let mut result = Self::default();
+        for (key, i) in data {
+            result.insert(key, i);
+        }
+        result
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<usize> {
+        self.data.get(key).copied()
+    }
+
+    /// Gets the key as it is stored in the map together with its index.
+    pub(crate) fn get_key_value(&self, key: &str) -> Option<(&String, usize)> {
+        self.data.get_key_value(key).map(|(key, i)| (key, *i))
+    }
+
+    pub(crate) fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        String: Borrow<Q>,
+    {
+        self.data.contains_key(key)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Iterates over the indexes for changing them, in no particular order.
+    pub(crate) fn values_mut(&mut self) -> impl Iterator<Item = &mut usize> {
+        self.data.values_mut()
+    }
+
+    /// Gets the keys in the order in which they were added.
+    pub(crate) fn keys(&self) -> &[String] {
+        self.keys.as_slice()
+    }
+
+    /// Iterates over the keys and the indexes in the order in which the keys were added.
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (&String, usize)> {
+        self.keys.iter().map(|key| (key, self.data[key]))
+    }
+
+    /// Sets the index for the key, a new key goes after the existing ones.
+    ///
+    /// Returns the previous index for the key.
+    pub(crate) fn insert(&mut self, key: String, index: usize) -> Option<usize> {
+        let result = self.data.insert(key.clone(), index);
+        if result.is_none() {
+            self.keys.push(key);
+        }
+        result
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<usize> {
+        let result = self.data.remove(key);
+        if result.is_some() {
+            self.keys.retain(|i| i != key);
+        }
+        result
+    }
+
+    pub(crate) fn retain<F: FnMut(&String, usize) -> bool>(&mut self, mut f: F) {
+        self.data.retain(|key, i| f(key, *i));
+        let data = &self.data;
+        self.keys.retain(|key| data.contains_key(key));
+    }
+}
+
+impl Index<&str> for MapNode {
+    type Output = usize;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        &self.data[key]
+    }
+}
+
+impl PartialEq for MapNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
     }
 }
@@ -3,5 +3,6 @@ pub(crate) mod get_anchor_node;
 pub(crate) mod list_node;
 pub(crate) mod map_node;
 pub(crate) mod node;
+pub(crate) mod string_node;
 pub(crate) mod tag_node;
 pub(crate) mod take_anchor_node;
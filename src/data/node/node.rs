@@ -1,11 +1,10 @@
 use super::super::mark::Mark;
 pub(crate) use super::{
     file_node::FileNode, get_anchor_node::GetAnchorNode, list_node::ListNode, map_node::MapNode,
-    tag_node::TaggedNode, take_anchor_node::TakeAnchorNode,
+    string_node::StringNode, tag_node::TaggedNode, take_anchor_node::TakeAnchorNode,
 };
 
 pub(crate) type RawNode = String;
-pub(crate) type StringNode = String;
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub(crate) enum Node {
@@ -21,14 +20,41 @@ pub(crate) enum Node {
     GetAnchor(GetAnchorNode),
 }
 
+impl Node {
+    /// Replaces the indexes of the nodes this node refers to.
+    pub(crate) fn remap<F: Fn(usize) -> usize>(&mut self, f: F) {
+        let remap_map = |map: &mut MapNode| map.values_mut().for_each(|i| *i = f(*i));
+        match self {
+            Node::List(i) => i.data.iter_mut().for_each(|i| *i = f(*i)),
+            Node::Map(i) => remap_map(i),
+            Node::Tagged(i) => i.node_index = f(i.node_index),
+            Node::File(i) => {
+                i.node_index = f(i.node_index);
+                remap_map(&mut i.anchors);
+                remap_map(&mut i.file_anchors);
+                i.parent = i.parent.map(&f);
+            }
+            Node::TakeAnchor(i) => i.node_index = f(i.node_index),
+            Node::GetAnchor(i) => i.node_index = f(i.node_index),
+            Node::Null | Node::Raw(_) | Node::String(_) => {}
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub(crate) struct MarkedNode {
     pub node: Node,
     pub mark: Mark,
+    /// The mark after the last symbol of the node, if the node was read from the source.
+    pub end: Option<Mark>,
 }
 
 impl MarkedNode {
     pub fn new(node: Node, mark: Mark) -> Self {
-        Self { node, mark }
+        Self {
+            node,
+            mark,
+            end: None,
+        }
     }
 }
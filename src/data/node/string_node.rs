@@ -0,0 +1,26 @@
+use super::super::string_style::StringStyle;
+
+/// The style is not taken into account when comparing strings.
+#[derive(Debug, Clone, Eq, Default)]
+pub(crate) struct StringNode {
+    pub(crate) string: String,
+    pub(crate) style: StringStyle,
+}
+
+impl StringNode {
+    pub(crate) fn new(string: String, style: StringStyle) -> Self {
+        Self { string, style }
+    }
+}
+
+impl From<&str> for StringNode {
+    fn from(value: &str) -> Self {
+        Self::new(value.into(), Default::default())
+    }
+}
+
+impl PartialEq for StringNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.string == other.string
+    }
+}
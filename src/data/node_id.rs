@@ -0,0 +1,9 @@
+/// Handle of a node of a `Data`, for keeping track of nodes without borrowing the `Data`.
+///
+/// The handle stays valid while the node is in the document, also when the node is put under a
+/// tag or an anchor. The handle of a replaced node refers to the node that replaced it.
+///
+/// The root is kept after all other nodes, so adding nodes gives it a new handle and the old one
+/// refers to no node. `Data::compact` renumbers the nodes and invalidates all handles.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct NodeId(pub(crate) usize);
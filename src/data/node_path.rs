@@ -0,0 +1,234 @@
+use super::error::InvalidPathError;
+use std::{
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// One step of a logical path through the document.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+    Tag(String),
+    File(PathBuf),
+}
+
+/// Logical path from one node of the document to another.
+#[derive(Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub struct NodePath {
+    segments: Vec<PathSegment>,
+}
+
+impl NodePath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        self.segments.as_slice()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    pub fn join(&self, other: &NodePath) -> NodePath {
+        let mut segments = self.segments.clone();
+        segments.extend(other.segments.iter().cloned());
+        Self { segments }
+    }
+}
+
+impl From<Vec<PathSegment>> for NodePath {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        Self { segments }
+    }
+}
+
+impl FromIterator<PathSegment> for NodePath {
+    fn from_iter<I: IntoIterator<Item = PathSegment>>(iter: I) -> Self {
+        Self {
+            segments: iter.into_iter().collect(),
+        }
+    }
+}
+
+pub(crate) fn is_plain_char(c: char) -> bool {
+    !c.is_whitespace() && !".[]()<>\"\\".contains(c)
+}
+
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(is_plain_char)
+}
+
+/// Reads a key written in quotes with escapes, returns the key and the length of its text.
+pub(crate) fn quoted_key(text: &str) -> Option<(String, usize)> {
+    let mut result = String::new();
+    let mut chars = text.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((result, i + 1)),
+            '\\' => result.push(match chars.next()?.1 {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                '0' => '\0',
+                'u' => {
+                    chars.next().filter(|(_, c)| *c == '{')?;
+                    let code = chars
+                        .by_ref()
+                        .map(|(_, c)| c)
+                        .take_while(|c| *c != '}')
+                        .collect::<String>();
+                    char::from_u32(u32::from_str_radix(&code, 16).ok()?)?
+                }
+                c @ ('\\' | '"' | '\'') => c,
+                _ => return None,
+            }),
+            c => result.push(c),
+        }
+    }
+    None
+}
+
+/// Reads a plain or a quoted key, returns the key and the length of its text.
+fn key(text: &str) -> Option<(String, usize)> {
+    if text.starts_with('"') {
+        return quoted_key(text);
+    }
+    let len = text.find(|c| !is_plain_char(c)).unwrap_or(text.len());
+    (len != 0).then(|| (text[..len].to_string(), len))
+}
+
+impl FromStr for NodePath {
+    type Err = InvalidPathError;
+
+    /// Parses a path of keys and indexes written the way it is displayed, for example
+    /// `servers[2].listen.port`.
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let error = |rest: &str| InvalidPathError::new(path.into(), path.len() - rest.len());
+        let mut segments = Vec::new();
+        let mut rest = path;
+        while !rest.is_empty() {
+            if let Some(index) = rest.strip_prefix('[') {
+                let end = index.find(']').ok_or_else(|| error(rest))?;
+                let digits = &index[..end];
+                if digits.is_empty() || !digits.chars().all(|i| i.is_ascii_digit()) {
+                    return Err(error(rest));
+                }
+                let index = digits.parse().map_err(|_| error(rest))?;
+                segments.push(PathSegment::Index(index));
+                rest = &rest[end + 2..];
+                continue;
+            }
+            let text = match rest.strip_prefix('.') {
+                Some(text) if !segments.is_empty() => text,
+                None if segments.is_empty() => rest,
+                _ => return Err(error(rest)),
+            };
+            let (key, len) = key(text).ok_or_else(|| error(text))?;
+            segments.push(PathSegment::Key(key));
+            rest = &text[len..];
+        }
+        Ok(Self { segments })
+    }
+}
+
+impl Display for NodePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) => {
+                    if i != 0 {
+                        write!(f, ".")?;
+                    }
+                    match is_plain_key(key) {
+                        true => write!(f, "{}", key)?,
+                        false => write!(f, "{:?}", key)?,
+                    }
+                }
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                PathSegment::Tag(tag) => write!(f, "({})", tag)?,
+                PathSegment::File(path) => write!(f, "<{}>", path.display())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Position of a view in the map or list it was obtained from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Segment<'data> {
+    Key(&'data str),
+    Index(usize),
+}
+
+impl<'data> From<Segment<'data>> for PathSegment {
+    fn from(value: Segment<'data>) -> Self {
+        match value {
+            Segment::Key(i) => PathSegment::Key(i.into()),
+            Segment::Index(i) => PathSegment::Index(i),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_path_display() {
+        let path = NodePath::from(vec![
+            PathSegment::Key("servers".into()),
+            PathSegment::Index(3),
+            PathSegment::Tag("server".into()),
+            PathSegment::Key("listen".into()),
+            PathSegment::File("listen.ieml".into()),
+            PathSegment::Key("port".into()),
+        ]);
+        assert_eq!(
+            path.to_string(),
+            "servers[3](server).listen<listen.ieml>.port"
+        );
+
+        let path = NodePath::from(vec![
+            PathSegment::Index(0),
+            PathSegment::Key("a.b".into()),
+            PathSegment::Key("".into()),
+        ]);
+        assert_eq!(path.to_string(), "[0].\"a.b\".\"\"");
+        assert_eq!(NodePath::new().to_string(), "");
+    }
+
+    #[test]
+    fn test_node_path_parse() {
+        let path = NodePath::from(vec![
+            PathSegment::Key("servers".into()),
+            PathSegment::Index(2),
+            PathSegment::Index(0),
+            PathSegment::Key("a.b".into()),
+            PathSegment::Key("\"\n\u{1}".into()),
+            PathSegment::Key("port".into()),
+        ]);
+        assert_eq!(path.to_string().parse(), Ok(path));
+        assert_eq!(
+            "[1]".parse(),
+            Ok(NodePath::from(vec![PathSegment::Index(1)]))
+        );
+        assert_eq!("".parse(), Ok(NodePath::new()));
+
+        let error = |path: &str, position| Err(InvalidPathError::new(path.into(), position));
+        assert_eq!("a..b".parse::<NodePath>(), error("a..b", 2));
+        assert_eq!(".a".parse::<NodePath>(), error(".a", 0));
+        assert_eq!("a[1]b".parse::<NodePath>(), error("a[1]b", 4));
+        assert_eq!("a[-1]".parse::<NodePath>(), error("a[-1]", 1));
+        assert_eq!("a[1".parse::<NodePath>(), error("a[1", 1));
+        assert_eq!("a.\"b".parse::<NodePath>(), error("a.\"b", 2));
+        assert_eq!("a b".parse::<NodePath>(), error("a b", 1));
+    }
+}
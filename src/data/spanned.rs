@@ -0,0 +1,156 @@
+use super::{
+    error::marked,
+    mark::{Mark, Span},
+    view::{analyse_anchors::AnalyseAnchors, deserialize::Deserialize, view::View},
+};
+use std::{
+    error::Error,
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+};
+
+/// A decoded value together with the location of the node it was decoded from.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Spanned<T> {
+    value: T,
+    mark: Mark,
+    file_path: PathBuf,
+    span: Option<Span>,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(value: T, mark: Mark, file_path: PathBuf, span: Option<Span>) -> Self {
+        Self {
+            value,
+            mark,
+            file_path,
+            span,
+        }
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn value_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    pub fn mark(&self) -> Mark {
+        self.mark
+    }
+
+    pub fn file_path(&self) -> &Path {
+        self.file_path.as_path()
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Spanned<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<'data, A, E, T> Deserialize<'data, A, E> for Spanned<T>
+where
+    A: AnalyseAnchors<'data>,
+    E: Error + PartialEq + Eq,
+    T: Deserialize<'data, A, E>,
+{
+    fn deserialize(view: View<'data, A>) -> Result<Self, marked::DeserializeError<E>> {
+        let (mark, span) = (view.mark(), view.span());
+        let file_path = view.file_path().to_path_buf();
+        T::deserialize(view).map(|value| Self::new(value, mark, file_path, span))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::make::{self, error::marked::MakeResult, Maker};
+    use super::*;
+    use crate::cst::document::Cst;
+    use std::convert::Infallible;
+
+    #[test]
+    fn test_spanned() {
+        let begin_mark = Mark::default();
+        let value_mark = Mark::new(2, 4);
+        let data = make::make::<_, Infallible, _>(begin_mark, {
+            make::file(
+                begin_mark,
+                (),
+                "dir/name.ieml".into(),
+                Vec::<(&str, Box<dyn FnOnce(&mut Maker) -> MakeResult<_, Infallible>>)>::new()
+                    .into_iter(),
+                make::tag(begin_mark, "tag", make::raw(value_mark, (), "15")),
+            )
+        })
+        .unwrap();
+        let view = data.view().clear_step_file().unwrap();
+        let tagged_view = view.clear_step_file().unwrap();
+
+        let spanned = tagged_view.decode::<Infallible, Spanned<u8>>().unwrap();
+        assert_eq!(*spanned, 15);
+        assert_eq!(spanned.mark(), begin_mark);
+        assert_eq!(spanned.file_path(), Path::new("dir/name.ieml"));
+        assert_eq!(spanned.span(), None);
+
+        let raw_view = tagged_view.clear_step_tagged().unwrap();
+        let spanned = raw_view.decode::<Infallible, Spanned<u8>>().unwrap();
+        assert_eq!(spanned.mark(), value_mark);
+        assert_eq!(spanned.file_path(), Path::new("dir/name.ieml"));
+
+        let spanned = view.decode::<Infallible, Spanned<u8>>().unwrap();
+        assert_eq!(spanned.file_path(), Path::new(""));
+        assert_eq!(spanned.into_value(), 15);
+    }
+
+    #[test]
+    fn test_spanned_source() {
+        let data = Cst::parse("test.ieml", "port: 8080\n")
+            .unwrap()
+            .to_data()
+            .unwrap();
+        let port = data.view().map().unwrap().get("port").unwrap();
+        let spanned = port.decode::<Infallible, Spanned<u16>>().unwrap();
+        let span = Span::new(Mark::new(0, 6), Mark::new(0, 10));
+        assert_eq!(spanned.span(), Some(span));
+    }
+
+    #[test]
+    fn test_spanned_list() {
+        let source = "ports:\n\t- 80\n\t- 8080\n";
+        let mut data = Cst::parse("dir/test.ieml", source)
+            .unwrap()
+            .to_data()
+            .unwrap();
+        let mut root = data.view_mut();
+        let mut ports = root.get("ports").unwrap();
+        let port = make::raw::<_, Infallible, _>(Mark::default(), (), "443");
+        ports.push(port).unwrap();
+
+        let ports = data.view().at("ports").unwrap();
+        let ports = ports.decode::<Infallible, Vec<Spanned<u16>>>().unwrap();
+        let values = ports.iter().map(|i| **i).collect::<Vec<_>>();
+        assert_eq!(values, [80, 8080, 443]);
+        let path = Path::new("dir/test.ieml");
+        assert!(ports.iter().all(|i| i.file_path() == path));
+        assert_eq!(ports[2].span(), None);
+    }
+}
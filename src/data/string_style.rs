@@ -0,0 +1,40 @@
+/// Form a string was written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum StringStyle {
+    /// `> string` up to the end of the line.
+    #[default]
+    Line,
+    /// `>>` followed by indented lines.
+    NotEscaped,
+    /// `"string"`, `escaped` is whether escape sequences were used.
+    Classic { escaped: bool },
+}
+
+impl StringStyle {
+    /// Whether a string can be written in this form without changing the form.
+    pub fn fits(&self, string: &str) -> bool {
+        match self {
+            StringStyle::Line => !string.contains('\n'),
+            StringStyle::NotEscaped => true,
+            StringStyle::Classic { escaped: true } => true,
+            StringStyle::Classic { escaped: false } => !string.contains(['"', '\\']),
+        }
+    }
+
+    /// Chooses the preferred form for the string based on its contents.
+    ///
+    /// Strings with whitespace at the edges of a line or with control characters are written
+    /// escaped, since it would be lost or invisible otherwise, other strings with several lines
+    /// are written with `>>`, the rest with `>`.
+    pub fn preferred(string: &str) -> Self {
+        let edges = |i: &str| i.is_empty() || i.trim() != i;
+        let control = string.chars().any(|i| i.is_control() && i != '\n');
+        if control || edges(string) || string.split('\n').any(|i| i != i.trim_end()) {
+            StringStyle::Classic { escaped: true }
+        } else if string.contains('\n') {
+            StringStyle::NotEscaped
+        } else {
+            StringStyle::Line
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use super::{
+    data::Data,
+    error::marked,
+    make::{self, error::marked::MakeResult, Maker},
+    mark::Mark,
+    node_type::NodeType,
+    view::{
+        analyse_anchors::AnalyseAnchors, clear, collect::Collector, deserialize::Deserialize,
+        view::View, ToMatchView,
+    },
+};
+use std::{collections::HashMap, convert::Infallible, error::Error, ops::Index};
+
+/// Owned value of a document node, with anchors and files resolved.
+///
+/// Map entries keep the order they are written in.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub enum Value {
+    #[default]
+    Null,
+    Raw(String),
+    String(String),
+    List(Vec<MarkedValue>),
+    Map(Vec<(String, MarkedValue)>),
+    Tagged(String, Box<MarkedValue>),
+}
+
+/// Owned value with the mark of the node it was decoded from, if any.
+///
+/// Marks are not taken into account when comparing values.
+#[derive(Clone, Eq, Default, Debug)]
+pub struct MarkedValue {
+    pub value: Value,
+    pub mark: Option<Mark>,
+}
+
+static NULL: MarkedValue = MarkedValue {
+    value: Value::Null,
+    mark: None,
+};
+
+type MakeValue<'a, E> = Box<dyn FnOnce(&mut Maker) -> MakeResult<(), E> + 'a>;
+
+impl Value {
+    pub fn tagged<S: Into<String>, V: Into<MarkedValue>>(tag: S, value: V) -> Self {
+        Value::Tagged(tag.into(), Box::new(value.into()))
+    }
+
+    pub fn node_type(&self) -> NodeType {
+        match self {
+            Value::Null => NodeType::Null,
+            Value::Raw(_) => NodeType::Raw,
+            Value::String(_) => NodeType::String,
+            Value::List(_) => NodeType::List,
+            Value::Map(_) => NodeType::Map,
+            Value::Tagged(_, _) => NodeType::Tagged,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_raw(&self) -> Option<&str> {
+        match self {
+            Value::Raw(i) => Some(i.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<&str> {
+        match self {
+            Value::String(i) => Some(i.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&Vec<MarkedValue>> {
+        match self {
+            Value::List(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&[(String, MarkedValue)]> {
+        match self {
+            Value::Map(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    pub fn as_tagged(&self) -> Option<(&str, &MarkedValue)> {
+        match self {
+            Value::Tagged(tag, i) => Some((tag.as_str(), i)),
+            _ => None,
+        }
+    }
+
+    /// Gets the value under all the tags.
+    pub fn clear_tag(&self) -> &Value {
+        match self {
+            Value::Tagged(_, i) => i.value.clear_tag(),
+            i => i,
+        }
+    }
+
+    /// Gets the value of the map by key, looking through tags.
+    pub fn get(&self, key: &str) -> Option<&MarkedValue> {
+        let map = self.clear_tag().as_map()?;
+        map.iter().find(|(i, _)| i == key).map(|(_, i)| i)
+    }
+
+    /// Gets the item of the list by index, looking through tags.
+    pub fn get_index(&self, index: usize) -> Option<&MarkedValue> {
+        self.clear_tag().as_list().and_then(|i| i.get(index))
+    }
+
+    /// Builds the value with the make combinators.
+    pub fn make<'a, E: Error + PartialEq + Eq + 'a>(
+        &'a self,
+        mark: Mark,
+    ) -> impl FnOnce(&mut Maker) -> MakeResult<(), E> + 'a {
+        move |maker| match self {
+            Value::Null => make::null(mark, ())(maker),
+            Value::Raw(i) => make::raw(mark, (), i.as_str())(maker),
+            Value::String(i) => make::string(mark, (), i.as_str())(maker),
+            Value::List(i) => {
+                let iter = i.iter().map(|i| Box::new(i.make()) as MakeValue<E>);
+                make::list(mark, (), iter)(maker)
+            }
+            Value::Map(i) => {
+                let iter = i
+                    .iter()
+                    .map(|(key, i)| (key.as_str(), Box::new(i.make()) as MakeValue<E>));
+                make::map(mark, (), iter)(maker)
+            }
+            Value::Tagged(tag, i) => {
+                make::tag(mark, tag.as_str(), Box::new(i.make()) as MakeValue<E>)(maker)
+            }
+        }
+    }
+
+    /// Creates a document containing only this value.
+    pub fn to_data(&self) -> Data {
+        MarkedValue::from(self.clone()).to_data()
+    }
+}
+
+impl MarkedValue {
+    pub fn new(value: Value, mark: Option<Mark>) -> Self {
+        Self { value, mark }
+    }
+
+    /// Builds the value with the make combinators, using the default mark for values without one.
+    pub fn make<'a, E: Error + PartialEq + Eq + 'a>(
+        &'a self,
+    ) -> impl FnOnce(&mut Maker) -> MakeResult<(), E> + 'a {
+        self.value.make(self.mark.unwrap_or_default())
+    }
+
+    /// Creates a document containing only this value.
+    pub fn to_data(&self) -> Data {
+        make::make::<_, Infallible, _>(self.mark.unwrap_or_default(), self.make())
+            .expect("Internal error, a value without anchors cannot fail to be made.")
+    }
+}
+
+impl PartialEq for MarkedValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<'a> Index<&'a str> for Value {
+    type Output = MarkedValue;
+
+    fn index(&self, index: &'a str) -> &Self::Output {
+        self.get(index).unwrap_or(&NULL)
+    }
+}
+
+impl Index<usize> for Value {
+    type Output = MarkedValue;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+impl<'a> Index<&'a str> for MarkedValue {
+    type Output = MarkedValue;
+
+    fn index(&self, index: &'a str) -> &Self::Output {
+        &self.value[index]
+    }
+}
+
+impl Index<usize> for MarkedValue {
+    type Output = MarkedValue;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.value[index]
+    }
+}
+
+impl<T: Into<Value>> From<T> for MarkedValue {
+    fn from(value: T) -> Self {
+        Self::new(value.into(), None)
+    }
+}
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::Null
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Raw(if value { "yes" } else { "no" }.into())
+    }
+}
+
+macro_rules! impl_number_from {
+	($($T:ty),*) => {
+		$(
+			impl From<$T> for Value {
+				fn from(value: $T) -> Self {
+					Value::Raw(value.to_string())
+				}
+			}
+		)*
+	};
+}
+
+impl_number_from!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64);
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<T: Into<MarkedValue>> From<Vec<T>> for Value {
+    fn from(value: Vec<T>) -> Self {
+        Value::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<S: Into<String>, T: Into<MarkedValue>> From<HashMap<S, T>> for Value {
+    fn from(value: HashMap<S, T>) -> Self {
+        Value::Map(
+            value
+                .into_iter()
+                .map(|(key, i)| (key.into(), i.into()))
+                .collect(),
+        )
+    }
+}
+
+impl<'data, A, E> Deserialize<'data, A, E> for Value
+where
+    A: AnalyseAnchors<'data>,
+    E: Error + PartialEq + Eq,
+{
+    fn deserialize(view: View<'data, A>) -> Result<Self, marked::DeserializeError<E>> {
+        MarkedValue::deserialize(view).map(|i| i.value)
+    }
+}
+
+impl<'data, A, E> Deserialize<'data, A, E> for MarkedValue
+where
+    A: AnalyseAnchors<'data>,
+    E: Error + PartialEq + Eq,
+{
+    fn deserialize(view: View<'data, A>) -> Result<Self, marked::DeserializeError<E>> {
+        let mark = Some(view.mark());
+        let clear = view.clear_advanced::<(clear::File, clear::TakeAnchor, clear::GetAnchor)>();
+        let value = match clear.to_match() {
+            ToMatchView::Null(_) => Value::Null,
+            ToMatchView::Raw(i) => Value::Raw(i.raw().into()),
+            ToMatchView::String(i) => Value::String(i.string().into()),
+            ToMatchView::List(i) => {
+                let mut collector = Collector::new();
+                let values = i.iter().map(|i| collector.decode(&i)).collect::<Vec<_>>();
+                Value::List(collector.finish(values)?)
+            }
+            ToMatchView::Map(i) => {
+                let mut collector = Collector::new();
+                let values = i
+                    .iter()
+                    .map(|(key, i)| collector.decode(&i).map(|i| (key.clone(), i)))
+                    .collect::<Vec<_>>();
+                Value::Map(collector.finish(values)?)
+            }
+            ToMatchView::Tagged(i) => Value::Tagged(i.tag().into(), Box::new(i.view().decode()?)),
+            _ => panic!("Incorrect document structure, the node was not cleared."),
+        };
+        Ok(Self::new(value, mark))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cst::document::Cst;
+
+    #[test]
+    fn test_value() {
+        let value = Value::from(HashMap::from([
+            ("name", Value::from("main")),
+            ("port", Value::from(8080u16)),
+            ("enabled", Value::from(true)),
+            ("alias", Value::from(None::<String>)),
+            (
+                "servers",
+                Value::from(vec![Value::tagged("server", vec![1, 2])]),
+            ),
+        ]));
+        assert_eq!(value["name"].value.as_string(), Some("main"));
+        assert_eq!(value["port"].value.as_raw(), Some("8080"));
+        assert_eq!(value["enabled"].value.as_raw(), Some("yes"));
+        assert!(value["alias"].value.is_null());
+        assert!(value["missing"][3].value.is_null());
+        assert_eq!(value["servers"][0][1].value.as_raw(), Some("2"));
+        assert_eq!(value["servers"][0].value.node_type(), NodeType::Tagged);
+
+        let data = value.to_data();
+        let decoded = data.view().decode::<Infallible, MarkedValue>().unwrap();
+        assert_eq!(decoded.value, value);
+        assert_eq!(decoded.mark, Some(Mark::default()));
+        assert_eq!(
+            data.view()
+                .map()
+                .unwrap()
+                .get("port")
+                .unwrap()
+                .decode::<Infallible, u16>()
+                .unwrap(),
+            8080
+        );
+    }
+
+    #[test]
+    fn test_value_order() {
+        let source = "port: 8080\nname: > main\nalias: null\n";
+        let data = Cst::parse("", source).unwrap().to_data().unwrap();
+        let value = data.view().decode::<Infallible, Value>().unwrap();
+        let keys = value.as_map().unwrap().iter().map(|(i, _)| i.as_str());
+        assert_eq!(keys.collect::<Vec<_>>(), ["port", "name", "alias"]);
+        assert_eq!(value.to_data().to_string(), source);
+    }
+
+    #[test]
+    fn test_value_anchors() {
+        let begin_mark = Mark::default();
+        let value_mark = Mark::new(1, 2);
+        let data = make::make::<_, Infallible, _>(begin_mark, {
+            make::list(
+                begin_mark,
+                (),
+                vec![
+                    Box::new(make::take_anchor(
+                        value_mark,
+                        "anchor",
+                        make::string(value_mark, (), "hello"),
+                    )) as MakeValue<Infallible>,
+                    Box::new(make::get_anchor(begin_mark, (), "anchor")),
+                ]
+                .into_iter(),
+            )
+        })
+        .unwrap();
+
+        let value = data.view().decode::<Infallible, Value>().unwrap();
+        assert_eq!(value, Value::from(vec!["hello", "hello"]));
+        assert_eq!(value[0].mark, Some(value_mark));
+        assert_eq!(value[1].mark, Some(begin_mark));
+    }
+}
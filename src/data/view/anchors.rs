@@ -5,6 +5,7 @@ use super::{
         node::node::{FileNode, MarkedNode, Node},
     },
     analyse_anchors::AnalyseAnchors,
+    options::ViewOptions,
     type_view::map_view::MapView,
     view::View,
 };
@@ -16,6 +17,7 @@ pub struct Anchors<'data, A: AnalyseAnchors<'data>> {
     node: &'data FileNode,
     data: &'data Data,
     anchor_analyser: A,
+    options: ViewOptions,
 }
 
 impl<'data, A: AnalyseAnchors<'data>> Anchors<'data, A> {
@@ -24,22 +26,22 @@ impl<'data, A: AnalyseAnchors<'data>> Anchors<'data, A> {
         node: &'data FileNode,
         data: &'data Data,
         anchor_analyser: A,
+        options: ViewOptions,
     ) -> Self {
         Self {
             mark,
             node,
             data,
             anchor_analyser,
+            options,
         }
     }
 
     pub(crate) fn get_index(&self, key: &str) -> Option<usize> {
-        self.node.anchors.data.get(key).copied().or_else(|| {
+        self.node.anchors.get(key).or_else(|| {
             self.node
                 .file_anchors
-                .data
                 .get(key)
-                .copied()
                 .or_else(|| self.parent().and_then(|i| i.get_index(key)))
         })
     }
@@ -52,7 +54,8 @@ impl<'data, A: AnalyseAnchors<'data>> Anchors<'data, A> {
                 MarkedNode {
                     mark,
                     node: Node::File(node),
-                } => Self::new(*mark, node, self.data, parent),
+                    ..
+                } => Self::new(*mark, node, self.data, parent, self.options),
                 _ => panic!("Incorrect document structure, the parent view is not a File."),
             })
     }
@@ -60,20 +63,31 @@ impl<'data, A: AnalyseAnchors<'data>> Anchors<'data, A> {
     pub fn anchors(&self) -> MapView<'data, A> {
         let map_node = &self.node.anchors;
         let anchor_analyser = self.anchor_analyser.clone();
-        MapView::new(self.mark, map_node, self.data, anchor_analyser)
+        MapView::new(
+            self.mark,
+            map_node,
+            self.data,
+            anchor_analyser,
+            self.options,
+        )
     }
 
     pub fn file_anchors(&self) -> MapView<'data, A> {
         let map_node = &self.node.file_anchors;
         let anchor_analyser = self.anchor_analyser.clone();
-        MapView::new(self.mark, map_node, self.data, anchor_analyser)
+        MapView::new(
+            self.mark,
+            map_node,
+            self.data,
+            anchor_analyser,
+            self.options,
+        )
     }
 
     pub fn get(&self, key: &str) -> Option<View<'data, A>> {
         self.get_index(key).map(|i| {
-            let node = self.data.get(i);
             let anchor_analyser = self.anchor_analyser.clone();
-            View::new(node, self.data, anchor_analyser)
+            View::new(i, self.data, anchor_analyser, self.options)
         })
     }
 }
@@ -64,11 +64,19 @@ pub(crate) fn clear_step<'data, T: Clear<'data, A>, A: AnalyseAnchors<'data>>(
     T::clear(view)
 }
 
+/// Steps through the nodes while possible, a step back to a node already passed ends the clearing
+/// at the node the step was from, so cyclic references do not loop.
 pub(crate) fn clear<'data, T: Clear<'data, A>, A: AnalyseAnchors<'data>>(
     view: View<'data, A>,
 ) -> View<'data, A> {
-    match T::clear(view.clone()) {
-        Some(i) => clear::<T, A>(i),
-        None => view,
+    let mut passed = vec![view.index()];
+    let mut view = view;
+    while let Some(i) = T::clear(view.clone()) {
+        if passed.contains(&i.index()) {
+            break;
+        }
+        passed.push(i.index());
+        view = i;
     }
+    view
 }
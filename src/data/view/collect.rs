@@ -0,0 +1,260 @@
+use super::{
+    super::{
+        error::{marked, FailedDeserializeError},
+        node_path::{NodePath, PathSegment},
+    },
+    analyse_anchors::AnalyseAnchors,
+    deserialize::Deserialize,
+    type_view::{list_view::ListView, map_view::MapView},
+    view::View,
+};
+use std::error::Error;
+
+/// A set of optional decoding results that are all present if no errors were collected.
+pub trait Collected {
+    type Output;
+
+    fn collected(self) -> Option<Self::Output>;
+}
+
+impl<T> Collected for Option<T> {
+    type Output = T;
+
+    fn collected(self) -> Option<Self::Output> {
+        self
+    }
+}
+
+impl<T> Collected for Vec<Option<T>> {
+    type Output = Vec<T>;
+
+    fn collected(self) -> Option<Self::Output> {
+        self.into_iter().collect()
+    }
+}
+
+macro_rules! impl_collected {
+	($($name:ident)*) => {
+		impl<$($name),*> Collected for ($(Option<$name>, )*) {
+			type Output = ($($name, )*);
+
+			#[allow(non_snake_case)]
+			fn collected(self) -> Option<Self::Output> {
+				let ($($name, )*) = self;
+				Some(($($name?, )*))
+			}
+		}
+	}
+}
+
+impl_collected!(A1);
+impl_collected!(A1 A2);
+impl_collected!(A1 A2 A3);
+impl_collected!(A1 A2 A3 A4);
+impl_collected!(A1 A2 A3 A4 A5);
+impl_collected!(A1 A2 A3 A4 A5 A6);
+impl_collected!(A1 A2 A3 A4 A5 A6 A7);
+impl_collected!(A1 A2 A3 A4 A5 A6 A7 A8);
+impl_collected!(A1 A2 A3 A4 A5 A6 A7 A8 A9);
+impl_collected!(A1 A2 A3 A4 A5 A6 A7 A8 A9 A10);
+impl_collected!(A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11);
+impl_collected!(A1 A2 A3 A4 A5 A6 A7 A8 A9 A10 A11 A12);
+
+/// Decodes several values, collecting the errors of all of them instead of stopping at the first one.
+#[derive(PartialEq, Eq, Debug)]
+pub struct Collector<E: Error + PartialEq + Eq> {
+    errors: Vec<marked::DeserializeError<E>>,
+}
+
+impl<E: Error + PartialEq + Eq> Collector<E> {
+    pub fn new() -> Self {
+        Self { errors: Vec::new() }
+    }
+
+    /// Returns whether no errors have been collected.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn push<R: Into<marked::DeserializeError<E>>>(&mut self, error: R) {
+        self.errors.push(error.into());
+    }
+
+    /// Gets the value from the result, collecting the error if there is one.
+    pub fn collect<T, R>(&mut self, result: Result<T, R>) -> Option<T>
+    where
+        R: Into<marked::DeserializeError<E>>,
+    {
+        result.map_err(|e| self.push(e)).ok()
+    }
+
+    /// Decodes the view into type T.
+    pub fn decode<'data, A, T>(&mut self, view: &View<'data, A>) -> Option<T>
+    where
+        A: AnalyseAnchors<'data>,
+        T: Deserialize<'data, A, E>,
+    {
+        self.collect(view.decode())
+    }
+
+    /// Decodes the value of the map by key into type T.
+    pub fn field<'data, A, T>(&mut self, map: &MapView<'data, A>, key: &str) -> Option<T>
+    where
+        A: AnalyseAnchors<'data>,
+        T: Deserialize<'data, A, E>,
+    {
+        match map.get(key) {
+            Ok(view) => self.decode(&view),
+            Err(e) => {
+                // The error is given the path of the missing key rather than the path of the map.
+                let path = NodePath::from(vec![PathSegment::Key(key.into())]);
+                let mark = e.mark;
+                let error = FailedDeserializeError::new::<T>(path, Box::new(e.into()));
+                self.push(marked::WithMarkError::new(mark, error));
+                None
+            }
+        }
+    }
+
+    /// Decodes the item of the list by index into type T.
+    pub fn item<'data, A, T>(&mut self, list: &ListView<'data, A>, index: usize) -> Option<T>
+    where
+        A: AnalyseAnchors<'data>,
+        T: Deserialize<'data, A, E>,
+    {
+        let view = self.collect(list.get(index))?;
+        self.decode(&view)
+    }
+
+    /// Gets the collected errors as a single error, if there are any.
+    pub fn into_error(mut self) -> Option<marked::DeserializeError<E>> {
+        match self.errors.len() {
+            0 => None,
+            1 => self.errors.pop(),
+            _ => Some(marked::DeserializeError::Multiple(self.errors)),
+        }
+    }
+
+    /// Gets the values if no errors have been collected, otherwise all the collected errors.
+    pub fn finish<C: Collected>(self, values: C) -> Result<C::Output, marked::DeserializeError<E>> {
+        match self.into_error() {
+            Some(e) => Err(e),
+            None => Ok(values
+                .collected()
+                .expect("Internal error, a value is missing without an error.")),
+        }
+    }
+}
+
+impl<E: Error + PartialEq + Eq> Default for Collector<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{
+        data::Data,
+        mark::Mark,
+        node::node::{ListNode, MapNode, MarkedNode, Node},
+    };
+    use super::*;
+    use std::{collections::HashMap, convert::Infallible};
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Server {
+        enabled: bool,
+        port: u16,
+    }
+
+    impl<'data, A: AnalyseAnchors<'data>, E: Error + PartialEq + Eq> Deserialize<'data, A, E>
+        for Server
+    {
+        fn deserialize(view: View<'data, A>) -> Result<Self, marked::DeserializeError<E>> {
+            let map = view.map()?;
+            let mut collector = Collector::new();
+            let enabled = collector.field(&map, "enabled");
+            let port = collector.field(&map, "port");
+            collector
+                .finish((enabled, port))
+                .map(|(enabled, port)| Self { enabled, port })
+        }
+    }
+
+    fn test_data() -> Data {
+        Data::new([
+            MarkedNode::new(Node::Raw("yes".into()), Mark::new(1, 10)),
+            MarkedNode::new(Node::Raw("80".into()), Mark::new(2, 7)),
+            MarkedNode::new(
+                Node::Map(MapNode::new(HashMap::from([
+                    ("enabled".to_string(), 0),
+                    ("port".to_string(), 1),
+                ]))),
+                Mark::new(1, 2),
+            ),
+            MarkedNode::new(Node::Raw("maybe".into()), Mark::new(4, 10)),
+            MarkedNode::new(Node::Raw("eighty".into()), Mark::new(5, 7)),
+            MarkedNode::new(
+                Node::Map(MapNode::new(HashMap::from([
+                    ("enabled".to_string(), 3),
+                    ("port".to_string(), 4),
+                ]))),
+                Mark::new(4, 2),
+            ),
+            MarkedNode::new(Node::Map(MapNode::default()), Mark::new(6, 2)),
+            MarkedNode::new(Node::List(ListNode::new(vec![2, 5, 6])), Mark::new(0, 0)),
+        ])
+    }
+
+    #[test]
+    fn test_collector() {
+        let data = test_data();
+        let list = data.view().list().unwrap();
+
+        let mut collector = Collector::<Infallible>::new();
+        assert_eq!(
+            collector.item(&list, 0),
+            Some(Server {
+                enabled: true,
+                port: 80
+            })
+        );
+        assert!(collector.is_empty());
+        assert_eq!(collector.item::<_, Server>(&list, 1), None);
+        assert_eq!(collector.item::<_, Server>(&list, 3), None);
+        assert!(!collector.is_empty());
+
+        match collector.into_error() {
+            Some(marked::DeserializeError::Multiple(i)) => {
+                assert_eq!(i.len(), 2);
+                assert!(matches!(i[0], marked::DeserializeError::FailedDecode(_)));
+                assert!(matches!(i[1], marked::DeserializeError::InvalidIndex(_)));
+            }
+            i => panic!("Expected several errors, got {:?}", i),
+        }
+    }
+
+    #[test]
+    fn test_decode_all() {
+        let data = test_data();
+        let errors = data
+            .view()
+            .decode_all::<Infallible, Vec<Server>>()
+            .unwrap_err();
+
+        let errors = errors
+            .iter()
+            .map(|i| (i.data.get_full_path().to_string(), i.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(errors.len(), 4);
+        assert_eq!(errors[0].0, "[1].enabled");
+        assert!(errors[0].1.ends_with("\n4:10: Failed to convert view to 'bool'."));
+        assert_eq!(errors[1].0, "[1].port");
+        assert!(errors[1].1.ends_with("\n5:7: Failed to convert view to 'u16'."));
+        assert_eq!(errors[2].0, "[2].enabled");
+        assert!(errors[2].1.ends_with("6:2: A key named 'enabled' does not exist in the map."));
+        assert_eq!(errors[3].0, "[2].port");
+        assert!(errors[3].1.ends_with("6:2: A key named 'port' does not exist in the map."));
+    }
+}
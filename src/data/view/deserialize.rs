@@ -1,11 +1,12 @@
 use super::{
     super::error::marked,
     analyse_anchors::AnalyseAnchors,
+    collect::Collector,
     type_view::{list_view::ListView, map_view::MapView},
     view::View,
 };
 use crate::parse::utils::to_value::{to_bool, to_number};
-use std::error::Error;
+use std::{collections::HashMap, error::Error};
 
 pub trait Deserialize<'data, A: AnalyseAnchors<'data>, E: Error + PartialEq + Eq> {
     fn deserialize(view: View<'data, A>) -> Result<Self, marked::DeserializeError<E>>
@@ -55,3 +56,35 @@ impl<'data, A: AnalyseAnchors<'data>, E: Error + PartialEq + Eq> Deserialize<'da
         Ok(view.map()?)
     }
 }
+
+impl<'data, A, E, T> Deserialize<'data, A, E> for Vec<T>
+where
+    A: AnalyseAnchors<'data>,
+    E: Error + PartialEq + Eq,
+    T: Deserialize<'data, A, E>,
+{
+    fn deserialize(view: View<'data, A>) -> Result<Self, marked::DeserializeError<E>> {
+        let mut collector = Collector::new();
+        let items = view.list()?.iter().map(|i| collector.decode(&i)).collect();
+        collector.finish::<Vec<_>>(items)
+    }
+}
+
+impl<'data, A, E, T> Deserialize<'data, A, E> for HashMap<String, T>
+where
+    A: AnalyseAnchors<'data>,
+    E: Error + PartialEq + Eq,
+    T: Deserialize<'data, A, E>,
+{
+    fn deserialize(view: View<'data, A>) -> Result<Self, marked::DeserializeError<E>> {
+        let mut collector = Collector::new();
+        let items = view
+            .map()?
+            .iter()
+            .map(|(key, i)| collector.decode(&i).map(|i| (key.clone(), i)))
+            .collect();
+        collector
+            .finish::<Vec<_>>(items)
+            .map(|i| i.into_iter().collect())
+    }
+}
@@ -0,0 +1,253 @@
+use super::{
+    super::error::{marked, UnknownKeyError},
+    analyse_anchors::AnalyseAnchors,
+    collect::{Collected, Collector},
+    deserialize::Deserialize,
+    options::UnknownKeys,
+    type_view::map_view::MapView,
+    view::View,
+};
+use std::error::Error;
+
+/// Optimal string alignment distance between two strings.
+fn distance(first: &str, second: &str) -> usize {
+    let first = first.chars().collect::<Vec<_>>();
+    let second = second.chars().collect::<Vec<_>>();
+    let mut table = vec![vec![0; second.len() + 1]; first.len() + 1];
+    for (i, row) in table.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in table[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=first.len() {
+        for j in 1..=second.len() {
+            let cost = usize::from(first[i - 1] != second[j - 1]);
+            let mut value = (table[i - 1][j] + 1)
+                .min(table[i][j - 1] + 1)
+                .min(table[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && first[i - 1] == second[j - 2] && first[i - 2] == second[j - 1] {
+                value = value.min(table[i - 2][j - 2] + 1);
+            }
+            table[i][j] = value;
+        }
+    }
+    table[first.len()][second.len()]
+}
+
+/// Decodes the values of a map by key, remembering which keys were used.
+///
+/// Errors are collected as with [`Collector`], and keys of the map that were never requested are reported
+/// as [`UnknownKeyError`] if unknown keys are denied.
+pub struct Fields<'data, A: AnalyseAnchors<'data>, E: Error + PartialEq + Eq> {
+    map: MapView<'data, A>,
+    known_keys: Vec<String>,
+    unknown_keys: UnknownKeys,
+    collector: Collector<E>,
+}
+
+impl<'data, A: AnalyseAnchors<'data>, E: Error + PartialEq + Eq> Fields<'data, A, E> {
+    /// Creates fields that handle unknown keys as specified in the options of the map view.
+    pub fn new(map: MapView<'data, A>) -> Self {
+        Self {
+            unknown_keys: map.options().unknown_keys,
+            map,
+            known_keys: Vec::new(),
+            collector: Collector::new(),
+        }
+    }
+
+    /// Sets how to handle unknown keys for this value regardless of the options of the map view.
+    pub fn unknown_keys(mut self, unknown_keys: UnknownKeys) -> Self {
+        self.unknown_keys = unknown_keys;
+        self
+    }
+
+    pub fn map(&self) -> &MapView<'data, A> {
+        &self.map
+    }
+
+    pub fn collector(&mut self) -> &mut Collector<E> {
+        &mut self.collector
+    }
+
+    fn know(&mut self, key: &str) {
+        if !self.known_keys.iter().any(|i| i == key) {
+            self.known_keys.push(key.into());
+        }
+    }
+
+    /// Gets the view by key, marking the key as known.
+    pub fn get(&mut self, key: &str) -> Result<View<'data, A>, marked::InvalidKeyError> {
+        self.know(key);
+        self.map.get(key)
+    }
+
+    /// Decodes the value by key into type T, collecting the error if there is one.
+    pub fn field<T: Deserialize<'data, A, E>>(&mut self, key: &str) -> Option<T> {
+        self.know(key);
+        self.collector.field(&self.map, key)
+    }
+
+    /// Decodes the value by key into type T if the key exists, collecting the error if there is one.
+    pub fn optional<T: Deserialize<'data, A, E>>(&mut self, key: &str) -> Option<Option<T>> {
+        self.know(key);
+        match self.map.get(key) {
+            Ok(i) => self.collector.decode(&i).map(Some),
+            Err(_) => Some(None),
+        }
+    }
+
+    /// Gets the keys of the map that were not requested and their views, sorted by key.
+    pub fn unknown(&self) -> Vec<(&'data String, View<'data, A>)> {
+        let mut unknown = self
+            .map
+            .iter()
+            .filter(|(key, _)| !self.known_keys.contains(key))
+            .collect::<Vec<_>>();
+        unknown.sort_by_key(|(key, _)| *key);
+        unknown
+    }
+
+    fn closest_key(&self, key: &str) -> Option<String> {
+        let limit = (key.chars().count() / 3).max(1);
+        self.known_keys
+            .iter()
+            .map(|i| (distance(key, i), i))
+            .filter(|(distance, _)| *distance <= limit)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, i)| i.clone())
+    }
+
+    /// Gets the values if no errors have been collected and no unknown keys are denied, otherwise all the errors.
+    pub fn finish<C: Collected>(
+        mut self,
+        values: C,
+    ) -> Result<C::Output, marked::DeserializeError<E>> {
+        if self.unknown_keys == UnknownKeys::Deny {
+            for (key, view) in self.unknown() {
+                let error = UnknownKeyError::new(key.clone(), self.closest_key(key));
+                self.collector
+                    .push(marked::WithMarkError::new(view.mark(), error));
+            }
+        }
+        self.collector.finish(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        super::{
+            data::Data,
+            mark::Mark,
+            node::node::{MapNode, MarkedNode, Node},
+        },
+        options::ViewOptions,
+    };
+    use super::*;
+    use std::{collections::HashMap, convert::Infallible};
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Connection {
+        timeout: Option<u32>,
+        retries: u8,
+    }
+
+    impl<'data, A: AnalyseAnchors<'data>, E: Error + PartialEq + Eq> Deserialize<'data, A, E>
+        for Connection
+    {
+        fn deserialize(view: View<'data, A>) -> Result<Self, marked::DeserializeError<E>> {
+            let mut fields = Fields::new(view.map()?);
+            let timeout = fields.optional("timeout");
+            let retries = fields.field("retries");
+            fields
+                .finish((timeout, retries))
+                .map(|(timeout, retries)| Self { timeout, retries })
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Lenient {
+        retries: u8,
+    }
+
+    impl<'data, A: AnalyseAnchors<'data>, E: Error + PartialEq + Eq> Deserialize<'data, A, E>
+        for Lenient
+    {
+        fn deserialize(view: View<'data, A>) -> Result<Self, marked::DeserializeError<E>> {
+            let mut fields = Fields::new(view.map()?).unknown_keys(UnknownKeys::Allow);
+            let retries = fields.field("retries");
+            fields.finish(retries).map(|retries| Self { retries })
+        }
+    }
+
+    fn test_data() -> Data {
+        Data::new([
+            MarkedNode::new(Node::Raw("5".into()), Mark::new(0, 8)),
+            MarkedNode::new(Node::Raw("3".into()), Mark::new(1, 9)),
+            MarkedNode::new(Node::Raw("no".into()), Mark::new(2, 7)),
+            MarkedNode::new(
+                Node::Map(MapNode::new(HashMap::from([
+                    ("timout".to_string(), 0),
+                    ("retries".to_string(), 1),
+                    ("verbose".to_string(), 2),
+                ]))),
+                Mark::new(0, 0),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("timeout", "timeout"), 0);
+        assert_eq!(distance("timout", "timeout"), 1);
+        assert_eq!(distance("prot", "port"), 1);
+        assert_eq!(distance("", "port"), 4);
+        assert_eq!(distance("retries", "verbose"), 5);
+    }
+
+    #[test]
+    fn test_allow_unknown_keys() {
+        let data = test_data();
+        let connection = data.view().decode::<Infallible, Connection>().unwrap();
+        assert_eq!(
+            connection,
+            Connection {
+                timeout: None,
+                retries: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_deny_unknown_keys() {
+        let data = test_data();
+        let view = data
+            .view()
+            .with_options(ViewOptions::new(UnknownKeys::Deny));
+
+        let errors = view.decode_all::<Infallible, Connection>().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].data.get_reason().as_ref(),
+            &marked::DeserializeError::UnknownKey(marked::WithMarkError::new(
+                Mark::new(0, 8),
+                UnknownKeyError::new("timout".into(), Some("timeout".into()))
+            ))
+        );
+        assert_eq!(
+            errors[1].data.get_reason().as_ref(),
+            &marked::DeserializeError::UnknownKey(marked::WithMarkError::new(
+                Mark::new(2, 7),
+                UnknownKeyError::new("verbose".into(), None)
+            ))
+        );
+        assert!(errors[0].to_string().ends_with(
+            "0:8: A key named 'timout' is not expected in the map. Perhaps you meant 'timeout'."
+        ));
+
+        let lenient = view.decode::<Infallible, Lenient>().unwrap();
+        assert_eq!(lenient, Lenient { retries: 3 });
+    }
+}
@@ -1,9 +1,15 @@
 pub mod analyse_anchors;
 pub mod anchors;
 pub mod clear;
+pub mod collect;
 pub mod deserialize;
+pub mod fields;
+pub mod options;
+pub mod select;
 pub mod to_match;
 pub mod type_view;
 pub mod view;
+pub mod view_mut;
+pub mod visit;
 
 pub use view::*;
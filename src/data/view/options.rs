@@ -0,0 +1,80 @@
+/// What to do with the keys of a map that were not used when decoding a value from it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum UnknownKeys {
+    #[default]
+    Allow,
+    Deny,
+}
+
+/// Bounds of a node with the anchors expanded, checked before decoding and before walking with
+/// the anchors followed, `None` is no bound.
+///
+/// Anchors referring to each other can make a small document expand exponentially, the bounds
+/// guard against allocating for all of it. Only the nodes reached through references are counted,
+/// so a document without anchors is never bounded. By default every bound is set.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ExpansionLimits {
+    /// The most nodes reached through references, a reference counts as a node.
+    pub max_nodes: Option<usize>,
+    /// The most references followed.
+    pub max_dereferences: Option<usize>,
+    /// The most levels of nodes under a reference, the reference counts as a level.
+    pub max_depth: Option<usize>,
+}
+
+impl ExpansionLimits {
+    pub const DEFAULT_MAX_NODES: usize = 1 << 20;
+    pub const DEFAULT_MAX_DEREFERENCES: usize = 1 << 20;
+    pub const DEFAULT_MAX_DEPTH: usize = 1 << 10;
+
+    pub fn new(
+        max_nodes: Option<usize>,
+        max_dereferences: Option<usize>,
+        max_depth: Option<usize>,
+    ) -> Self {
+        Self {
+            max_nodes,
+            max_dereferences,
+            max_depth,
+        }
+    }
+
+    /// Limits with no bound set, nothing is checked.
+    pub fn unlimited() -> Self {
+        Self::new(None, None, None)
+    }
+
+    pub(crate) fn is_unlimited(&self) -> bool {
+        *self == Self::unlimited()
+    }
+}
+
+impl Default for ExpansionLimits {
+    fn default() -> Self {
+        Self::new(
+            Some(Self::DEFAULT_MAX_NODES),
+            Some(Self::DEFAULT_MAX_DEREFERENCES),
+            Some(Self::DEFAULT_MAX_DEPTH),
+        )
+    }
+}
+
+/// Settings passed from a view to all the views obtained from it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ViewOptions {
+    pub unknown_keys: UnknownKeys,
+    pub limits: ExpansionLimits,
+}
+
+impl ViewOptions {
+    pub fn new(unknown_keys: UnknownKeys) -> Self {
+        Self {
+            unknown_keys,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_limits(self, limits: ExpansionLimits) -> Self {
+        Self { limits, ..self }
+    }
+}
@@ -0,0 +1,413 @@
+use super::{
+    super::{
+        error::InvalidSelectorError,
+        node_path::{is_plain_char, quoted_key, NodePath, PathSegment},
+        node_type::NodeType,
+    },
+    analyse_anchors::AnalyseAnchors,
+    view::View,
+};
+use std::{collections::HashSet, str::FromStr};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Matcher {
+    /// The node the step is taken from, used for predicates written before the first step.
+    This,
+    Key(String),
+    Index(usize),
+    /// Items of a list with indexes in the range, the range is open at the end.
+    Range(Option<usize>, Option<usize>),
+    /// Entries of a map and items of a list.
+    Any,
+    /// The node itself and all the nodes under it.
+    Descendants,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Predicate {
+    Tag(String),
+    Type(NodeType),
+}
+
+impl Predicate {
+    /// Checks the node and the nodes it consists of down to the value, such as Tagged and File.
+    fn matches<'data, A: AnalyseAnchors<'data>>(&self, view: &View<'data, A>) -> bool {
+        let mut chain = std::iter::successors(Some(view.clone()), |i| i.clear_step());
+        match self {
+            Predicate::Tag(tag) => chain.any(|i| {
+                i.node_type() == NodeType::Tagged && i.tagged().is_ok_and(|i| i.tag() == tag)
+            }),
+            Predicate::Type(node_type) => chain.any(|i| i.node_type() == *node_type),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Step {
+    matcher: Matcher,
+    predicates: Vec<Predicate>,
+}
+
+impl Step {
+    fn matches<'data, A: AnalyseAnchors<'data>>(&self, view: &View<'data, A>) -> bool {
+        self.predicates.iter().all(|i| i.matches(view))
+    }
+}
+
+/// Pattern that selects nodes of the document.
+///
+/// A selector is written as a path, each step of which may be one of:
+///
+/// * `key` or `"key"` for the entry of a map;
+/// * `[2]` for the item of a list, `[1..3]`, `[1..]`, `[..3]` or `[*]` for the items in a range;
+/// * `*` for every entry of a map or item of a list;
+/// * `**` for the node itself and every node under it.
+///
+/// A step may be followed by predicates, `(tag)` requires the node to be tagged with the tag
+/// and `:map` requires the node to be of the type, the names of the types are the names of
+/// `NodeType` variants in snake case. Predicates written before the first step apply to the node
+/// the selection starts from. For example, `services.*.timeout` or `**(secret)`.
+///
+/// A key containing `*` or `:` must be written in quotes.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Selector {
+    steps: Vec<Step>,
+}
+
+/// Result of parsing a part of a selector, the error holds the rest of the selector at which the
+/// invalid part begins.
+type Parsed<'a, T> = Result<(T, &'a str), &'a str>;
+
+fn node_type(name: &str) -> Option<NodeType> {
+    Some(match name {
+        "null" => NodeType::Null,
+        "raw" => NodeType::Raw,
+        "string" => NodeType::String,
+        "list" => NodeType::List,
+        "map" => NodeType::Map,
+        "tagged" => NodeType::Tagged,
+        "file" => NodeType::File,
+        "take_anchor" => NodeType::TakeAnchor,
+        "get_anchor" => NodeType::GetAnchor,
+        _ => return None,
+    })
+}
+
+fn parse_predicates(text: &str) -> Parsed<'_, Vec<Predicate>> {
+    let mut result = Vec::new();
+    let mut rest = text;
+    loop {
+        if let Some(text) = rest.strip_prefix('(') {
+            let end = text.find(')').filter(|i| *i != 0).ok_or(rest)?;
+            result.push(Predicate::Tag(text[..end].into()));
+            rest = &text[end + 1..];
+        } else if let Some(text) = rest.strip_prefix(':') {
+            let len = text
+                .find(|c: char| !c.is_ascii_lowercase() && c != '_')
+                .unwrap_or(text.len());
+            result.push(Predicate::Type(node_type(&text[..len]).ok_or(rest)?));
+            rest = &text[len..];
+        } else {
+            return Ok((result, rest));
+        }
+    }
+}
+
+fn index(text: &str) -> Option<usize> {
+    match !text.is_empty() && text.chars().all(|i| i.is_ascii_digit()) {
+        true => text.parse().ok(),
+        false => None,
+    }
+}
+
+/// Reads the matcher written in square brackets, without the brackets.
+fn parse_range(text: &str) -> Option<Matcher> {
+    let bound = |text: &str| match text {
+        "" => Some(None),
+        text => index(text).map(Some),
+    };
+    match text.split_once("..") {
+        _ if text == "*" => Some(Matcher::Range(None, None)),
+        Some((begin, end)) => Some(Matcher::Range(bound(begin)?, bound(end)?)),
+        None => index(text).map(Matcher::Index),
+    }
+}
+
+fn parse_matcher(text: &str) -> Parsed<'_, Matcher> {
+    if let Some(rest) = text.strip_prefix("**") {
+        return Ok((Matcher::Descendants, rest));
+    }
+    if let Some(rest) = text.strip_prefix('*') {
+        return Ok((Matcher::Any, rest));
+    }
+    if text.starts_with('"') {
+        let (key, len) = quoted_key(text).ok_or(text)?;
+        return Ok((Matcher::Key(key), &text[len..]));
+    }
+    let len = text
+        .find(|c| !is_plain_char(c) || c == '*' || c == ':')
+        .unwrap_or(text.len());
+    match len {
+        0 => Err(text),
+        len => Ok((Matcher::Key(text[..len].into()), &text[len..])),
+    }
+}
+
+impl FromStr for Selector {
+    type Err = InvalidSelectorError;
+
+    fn from_str(selector: &str) -> Result<Self, Self::Err> {
+        let error =
+            |rest: &str| InvalidSelectorError::new(selector.into(), selector.len() - rest.len());
+        let mut steps = Vec::new();
+        let (predicates, mut rest) = parse_predicates(selector).map_err(error)?;
+        if !predicates.is_empty() {
+            let matcher = Matcher::This;
+            steps.push(Step {
+                matcher,
+                predicates,
+            });
+        }
+        while !rest.is_empty() {
+            let (matcher, text) = match rest.strip_prefix('[') {
+                Some(text) => {
+                    let end = text.find(']').ok_or_else(|| error(rest))?;
+                    let matcher = parse_range(&text[..end]).ok_or_else(|| error(rest))?;
+                    (matcher, &text[end + 1..])
+                }
+                None => {
+                    let text = match rest.strip_prefix('.') {
+                        Some(text) if !steps.is_empty() => text,
+                        None if steps.is_empty() => rest,
+                        _ => return Err(error(rest)),
+                    };
+                    parse_matcher(text).map_err(error)?
+                }
+            };
+            let (predicates, text) = parse_predicates(text).map_err(error)?;
+            steps.push(Step {
+                matcher,
+                predicates,
+            });
+            rest = text;
+        }
+        Ok(Self { steps })
+    }
+}
+
+fn child_path(path: &NodePath, segment: PathSegment) -> NodePath {
+    let mut path = path.clone();
+    path.push(segment);
+    path
+}
+
+/// Gets the entries of the map or the items of the list under the node.
+///
+/// If `follow_anchors` is false, GetAnchor nodes are considered to have no children.
+fn children<'data, A: AnalyseAnchors<'data>>(
+    view: &View<'data, A>,
+    follow_anchors: bool,
+) -> Vec<(PathSegment, View<'data, A>)> {
+    let view = match follow_anchors {
+        true => view.clear(),
+        false => view.clear_get_anchor(),
+    };
+    match view.node_type() {
+        NodeType::Map => view
+            .map()
+            .into_iter()
+            .flat_map(|i| i.iter())
+            .map(|(key, i)| (PathSegment::Key(key.clone()), i))
+            .collect(),
+        NodeType::List => view
+            .list()
+            .into_iter()
+            .flat_map(|i| i.iter())
+            .enumerate()
+            .map(|(index, i)| (PathSegment::Index(index), i))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Iterator over the nodes matching a selector, in the order in which they appear in the document.
+///
+/// `**` does not descend into GetAnchor nodes, the nodes they refer to are found at the place
+/// where the anchor is taken. Each node is found once, even if several ways of the selector lead
+/// to it.
+pub struct Select<'data, 'selector, A: AnalyseAnchors<'data>> {
+    steps: &'selector [Step],
+    stack: Vec<(usize, NodePath, View<'data, A>)>,
+    found: HashSet<NodePath>,
+}
+
+impl<'data, 'selector, A: AnalyseAnchors<'data>> Select<'data, 'selector, A> {
+    pub(crate) fn new(selector: &'selector Selector, view: View<'data, A>) -> Self {
+        Self {
+            steps: selector.steps.as_slice(),
+            stack: vec![(0, NodePath::new(), view)],
+            found: HashSet::new(),
+        }
+    }
+
+    fn candidates(
+        matcher: &Matcher,
+        view: View<'data, A>,
+    ) -> Vec<(Option<PathSegment>, View<'data, A>)> {
+        let children = |follow_anchors| -> Vec<_> {
+            let children = children(&view, follow_anchors).into_iter();
+            children.map(|(segment, i)| (Some(segment), i)).collect()
+        };
+        match matcher {
+            Matcher::This => vec![(None, view.clone())],
+            Matcher::Key(key) => view
+                .map()
+                .ok()
+                .and_then(|i| i.get(key).ok())
+                .map(|i| (Some(PathSegment::Key(key.clone())), i))
+                .into_iter()
+                .collect(),
+            Matcher::Index(index) => view
+                .list()
+                .ok()
+                .and_then(|i| i.get(*index).ok())
+                .map(|i| (Some(PathSegment::Index(*index)), i))
+                .into_iter()
+                .collect(),
+            Matcher::Range(begin, end) => {
+                let (begin, end) = (begin.unwrap_or(0), end.unwrap_or(usize::MAX));
+                children(true)
+                    .into_iter()
+                    .filter(|(segment, _)| match segment {
+                        Some(PathSegment::Index(i)) => (begin..end).contains(i),
+                        _ => false,
+                    })
+                    .collect()
+            }
+            Matcher::Any => children(true),
+            Matcher::Descendants => children(false),
+        }
+    }
+}
+
+impl<'data, 'selector, A: AnalyseAnchors<'data>> Iterator for Select<'data, 'selector, A> {
+    type Item = (NodePath, View<'data, A>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((index, path, view)) = self.stack.pop() {
+            let Some(step) = self.steps.get(index) else {
+                if self.found.insert(path.clone()) {
+                    return Some((path, view));
+                }
+                continue;
+            };
+            let candidates = Self::candidates(&step.matcher, view.clone());
+            // The children of `**` stay at the same step, the node itself goes to the next one.
+            let next = match step.matcher {
+                Matcher::Descendants => index,
+                _ => index + 1,
+            };
+            for (segment, i) in candidates.into_iter().rev() {
+                if next == index || step.matches(&i) {
+                    let path = segment.map_or_else(|| path.clone(), |j| child_path(&path, j));
+                    self.stack.push((next, path, i));
+                }
+            }
+            if step.matcher == Matcher::Descendants && step.matches(&view) {
+                self.stack.push((index + 1, path, view));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cst::document::Cst, data::data::Data};
+
+    const SOURCE: &str = "services:
+	api:
+		timeout: 10
+		password: = secret: > hunter2
+	db:
+		timeout: 20
+		hosts:
+			- a
+			- &main b
+			- c
+main: *main
+token: = secret: abc
+";
+
+    fn data() -> Data {
+        Cst::parse("test.ieml", SOURCE).unwrap().to_data().unwrap()
+    }
+
+    fn select(data: &Data, selector: &str) -> Vec<String> {
+        let selector = selector.parse::<Selector>().unwrap();
+        let view = data.view();
+        view.select(&selector)
+            .map(|(path, i)| {
+                assert_eq!(view.at(&path.to_string()), Ok(i));
+                path.to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_select() {
+        let data = data();
+        let timeouts = ["services.api.timeout", "services.db.timeout"];
+        assert_eq!(select(&data, "services.*.timeout"), timeouts);
+        assert_eq!(select(&data, "**.timeout"), timeouts);
+        assert_eq!(select(&data, "**.**.timeout"), timeouts);
+        let secrets = ["services.api.password", "token"];
+        assert_eq!(select(&data, "**(secret)"), secrets);
+        assert_eq!(select(&data, "*(secret)"), ["token"]);
+        assert_eq!(select(&data, "**:string"), ["services.api.password"]);
+        let maps = ["", "services", "services.api", "services.db"];
+        assert_eq!(select(&data, "**:map"), maps);
+        assert_eq!(select(&data, ":map"), [""]);
+        assert_eq!(select(&data, "(secret)"), Vec::<String>::new());
+        assert_eq!(select(&data, "missing.*"), Vec::<String>::new());
+
+        let hosts = "services.db.hosts";
+        assert_eq!(
+            select(&data, "services.db.hosts[1]"),
+            [format!("{hosts}[1]")]
+        );
+        let tail = [format!("{hosts}[1]"), format!("{hosts}[2]")];
+        assert_eq!(select(&data, "services.db.hosts[1..]"), tail);
+        assert_eq!(
+            select(&data, "services.db.hosts[..1]"),
+            [format!("{hosts}[0]")]
+        );
+        assert_eq!(select(&data, "services.db.hosts[*]").len(), 3);
+        assert_eq!(select(&data, "services.db.*").len(), 2);
+    }
+
+    #[test]
+    fn test_select_anchors() {
+        let data = data();
+        assert_eq!(select(&data, "**:take_anchor"), ["services.db.hosts[1]"]);
+        assert_eq!(select(&data, "**:get_anchor"), ["main"]);
+        assert_eq!(select(&data, "*:raw"), ["main", "token"]);
+    }
+
+    #[test]
+    fn test_selector_error() {
+        let error = |selector: &str, position| {
+            let error = InvalidSelectorError::new(selector.into(), position);
+            assert_eq!(selector.parse::<Selector>(), Err(error));
+        };
+        error("a..b", 2);
+        error(".a", 0);
+        error("[x]", 0);
+        error("a[1", 1);
+        error(":number", 0);
+        error("a()", 1);
+        error("a(b", 1);
+        error("a*", 1);
+    }
+}
@@ -2,6 +2,7 @@ use super::super::{
     super::{data::Data, mark::Mark, node::file_node::FileNode},
     analyse_anchors::AnalyseAnchors,
     anchors::Anchors,
+    options::ViewOptions,
     view::View,
 };
 use std::{
@@ -15,6 +16,7 @@ pub struct FileView<'data, A: AnalyseAnchors<'data>> {
     node: &'data FileNode,
     data: &'data Data,
     anchor_analyser: A,
+    options: ViewOptions,
 }
 
 impl<'data, A: AnalyseAnchors<'data>> FileView<'data, A> {
@@ -23,12 +25,14 @@ impl<'data, A: AnalyseAnchors<'data>> FileView<'data, A> {
         node: &'data FileNode,
         data: &'data Data,
         anchor_analyser: A,
+        options: ViewOptions,
     ) -> Self {
         Self {
             mark,
             node,
             data,
             anchor_analyser,
+            options,
         }
     }
 
@@ -41,13 +45,23 @@ impl<'data, A: AnalyseAnchors<'data>> FileView<'data, A> {
     }
 
     pub fn view(&self) -> View<'data, A> {
-        let node = self.data.get(self.node.node_index);
-        View::new(node, self.data, self.anchor_analyser.clone())
+        View::new(
+            self.node.node_index,
+            self.data,
+            self.anchor_analyser.clone(),
+            self.options,
+        )
     }
 
     pub fn anchors(&self) -> Anchors<'data, A> {
         let anchor_analyser = self.anchor_analyser.clone();
-        Anchors::new(self.mark, self.node, self.data, anchor_analyser)
+        Anchors::new(
+            self.mark,
+            self.node,
+            self.data,
+            anchor_analyser,
+            self.options,
+        )
     }
 }
 
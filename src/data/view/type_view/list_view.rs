@@ -4,28 +4,37 @@ use super::super::{
         error::{marked, InvalidIndexError},
         mark::Mark,
         node::node::ListNode,
+        node_path::Segment,
     },
     analyse_anchors::AnalyseAnchors,
+    options::ViewOptions,
     view::View,
 };
 use std::{
     fmt::{self, Debug, Formatter},
-    slice,
+    iter, slice,
 };
 
 #[derive(Clone)]
 pub struct ListIter<'data, A: AnalyseAnchors<'data>> {
-    iter: slice::Iter<'data, usize>,
+    iter: iter::Enumerate<slice::Iter<'data, usize>>,
     data: &'data Data,
     anchor_analyser: A,
+    options: ViewOptions,
 }
 
 impl<'data, A: AnalyseAnchors<'data>> ListIter<'data, A> {
-    fn new(iter: slice::Iter<'data, usize>, data: &'data Data, anchor_analyser: A) -> Self {
+    fn new(
+        iter: slice::Iter<'data, usize>,
+        data: &'data Data,
+        anchor_analyser: A,
+        options: ViewOptions,
+    ) -> Self {
         Self {
             data,
-            iter,
+            iter: iter.enumerate(),
             anchor_analyser,
+            options,
         }
     }
 }
@@ -40,9 +49,9 @@ impl<'data, A: AnalyseAnchors<'data>> Iterator for ListIter<'data, A> {
     type Item = View<'data, A>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|i| {
-            let node = self.data.get(*i);
-            View::new(node, self.data, self.anchor_analyser.clone())
+        self.iter.next().map(|(index, i)| {
+            View::new(*i, self.data, self.anchor_analyser.clone(), self.options)
+                .with_segment(Segment::Index(index))
         })
     }
 }
@@ -53,6 +62,7 @@ pub struct ListView<'data, A: AnalyseAnchors<'data>> {
     node: &'data ListNode,
     data: &'data Data,
     anchor_analyser: A,
+    options: ViewOptions,
 }
 
 impl<'data, A: AnalyseAnchors<'data>> ListView<'data, A> {
@@ -61,12 +71,14 @@ impl<'data, A: AnalyseAnchors<'data>> ListView<'data, A> {
         node: &'data ListNode,
         data: &'data Data,
         anchor_analyser: A,
+        options: ViewOptions,
     ) -> Self {
         Self {
             mark,
             node,
             data,
             anchor_analyser,
+            options,
         }
     }
 
@@ -81,8 +93,8 @@ impl<'data, A: AnalyseAnchors<'data>> ListView<'data, A> {
     pub fn get(&self, index: usize) -> Result<View<'data, A>, marked::InvalidIndexError> {
         match self.node.data.get(index) {
             Some(i) => Ok({
-                let node = self.data.get(*i);
-                View::new(node, self.data, self.anchor_analyser.clone())
+                View::new(*i, self.data, self.anchor_analyser.clone(), self.options)
+                    .with_segment(Segment::Index(index))
             }),
             None => Err({
                 let error = InvalidIndexError::new(index, self.len());
@@ -93,7 +105,12 @@ impl<'data, A: AnalyseAnchors<'data>> ListView<'data, A> {
 
     pub fn iter(&self) -> ListIter<'data, A> {
         let anchor_analyser = self.anchor_analyser.clone();
-        ListIter::new(self.node.data.iter(), self.data, anchor_analyser)
+        ListIter::new(
+            self.node.data.iter(),
+            self.data,
+            anchor_analyser,
+            self.options,
+        )
     }
 }
 
@@ -121,7 +138,12 @@ impl<'data, A: AnalyseAnchors<'data>> IntoIterator for ListView<'data, A> {
     type Item = View<'data, A>;
 
     fn into_iter(self) -> Self::IntoIter {
-        ListIter::new(self.node.data.iter(), self.data, self.anchor_analyser)
+        ListIter::new(
+            self.node.data.iter(),
+            self.data,
+            self.anchor_analyser,
+            self.options,
+        )
     }
 }
 
@@ -145,7 +167,7 @@ mod tests {
     fn test_list_view() {
         let data = test_data();
         if let Node::List(node) = &data.get(2).node {
-            let list = ListView::new(Default::default(), node, &data, ());
+            let list = ListView::new(Default::default(), node, &data, (), Default::default());
 
             let first = list.get(0).unwrap();
             assert_eq!(first.node_type(), NodeType::String);
@@ -4,32 +4,39 @@ use super::super::{
         error::{marked, InvalidKeyError},
         mark::Mark,
         node::map_node::MapNode,
+        node_path::Segment,
     },
     analyse_anchors::AnalyseAnchors,
+    options::ViewOptions,
     view::View,
 };
 use std::{
-    collections::hash_map,
     fmt::{self, Debug, Formatter},
+    slice,
 };
 
 #[derive(Clone)]
 pub struct MapIter<'data, A: AnalyseAnchors<'data>> {
-    iter: hash_map::Iter<'data, String, usize>,
+    iter: slice::Iter<'data, String>,
+    node: &'data MapNode,
     data: &'data Data,
     anchor_analyser: A,
+    options: ViewOptions,
 }
 
 impl<'data, A: AnalyseAnchors<'data>> MapIter<'data, A> {
     fn new(
-        iter: hash_map::Iter<'data, String, usize>,
+        node: &'data MapNode,
         data: &'data Data,
         anchor_analyser: A,
+        options: ViewOptions,
     ) -> Self {
         Self {
             data,
-            iter,
+            iter: node.keys().iter(),
+            node,
             anchor_analyser,
+            options,
         }
     }
 }
@@ -44,9 +51,14 @@ impl<'data, A: AnalyseAnchors<'data>> Iterator for MapIter<'data, A> {
     type Item = (&'data String, View<'data, A>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|(key, i)| {
-            let node = self.data.get(*i);
-            let view = View::new(node, self.data, self.anchor_analyser.clone());
+        self.iter.next().map(|key| {
+            let view = View::new(
+                self.node[key.as_str()],
+                self.data,
+                self.anchor_analyser.clone(),
+                self.options,
+            )
+            .with_segment(Segment::Key(key.as_str()));
             (key, view)
         })
     }
@@ -58,6 +70,7 @@ pub struct MapView<'data, A: AnalyseAnchors<'data>> {
     node: &'data MapNode,
     data: &'data Data,
     anchor_analyser: A,
+    options: ViewOptions,
 }
 
 impl<'data, A: AnalyseAnchors<'data>> MapView<'data, A> {
@@ -66,12 +79,14 @@ impl<'data, A: AnalyseAnchors<'data>> MapView<'data, A> {
         node: &'data MapNode,
         data: &'data Data,
         anchor_analyser: A,
+        options: ViewOptions,
     ) -> Self {
         Self {
             mark,
             node,
             data,
             anchor_analyser,
+            options,
         }
     }
 
@@ -79,19 +94,23 @@ impl<'data, A: AnalyseAnchors<'data>> MapView<'data, A> {
         self.mark
     }
 
+    pub fn options(&self) -> ViewOptions {
+        self.options
+    }
+
     pub fn len(&self) -> usize {
-        self.node.data.len()
+        self.node.len()
     }
 
     pub fn contains_key(&self, key: &String) -> bool {
-        self.node.data.contains_key(key)
+        self.node.contains_key(key)
     }
 
     pub fn get(&self, key: &str) -> Result<View<'data, A>, marked::InvalidKeyError> {
-        match self.node.data.get(key) {
-            Some(i) => Ok({
-                let node = self.data.get(*i);
-                View::new(node, self.data, self.anchor_analyser.clone())
+        match self.node.get_key_value(key) {
+            Some((key, i)) => Ok({
+                View::new(i, self.data, self.anchor_analyser.clone(), self.options)
+                    .with_segment(Segment::Key(key.as_str()))
             }),
             None => Err({
                 let error = InvalidKeyError::new(key.into());
@@ -100,9 +119,10 @@ impl<'data, A: AnalyseAnchors<'data>> MapView<'data, A> {
         }
     }
 
+    /// Iterates over the entries in the order in which they appear in the source.
     pub fn iter(&self) -> MapIter<'data, A> {
         let anchor_analyser = self.anchor_analyser.clone();
-        MapIter::new(self.node.data.iter(), self.data, anchor_analyser)
+        MapIter::new(self.node, self.data, anchor_analyser, self.options)
     }
 }
 
@@ -136,16 +156,19 @@ impl<'data, A: AnalyseAnchors<'data>> IntoIterator for MapView<'data, A> {
     type Item = (&'data String, View<'data, A>);
 
     fn into_iter(self) -> Self::IntoIter {
-        MapIter::new(self.node.data.iter(), self.data, self.anchor_analyser)
+        MapIter::new(self.node, self.data, self.anchor_analyser, self.options)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::data::{
-        node::node::{MarkedNode, Node, TaggedNode},
-        node_type::NodeType,
+    use crate::{
+        cst::document::Cst,
+        data::{
+            node::node::{MarkedNode, Node, TaggedNode},
+            node_type::NodeType,
+        },
     };
     use std::collections::HashMap;
 
@@ -171,7 +194,7 @@ mod tests {
     fn test_map_view() {
         let data = test_data();
         if let Node::Map(node) = &data.get(3).node {
-            let list = MapView::new(Default::default(), node, &data, ());
+            let list = MapView::new(Default::default(), node, &data, (), Default::default());
 
             let first = list.get("first").unwrap();
             assert_eq!(first.node_type(), NodeType::Null);
@@ -190,4 +213,18 @@ mod tests {
             panic!("The node is not a map");
         }
     }
+
+    #[test]
+    fn test_map_order() {
+        let source = "port: 8080\nhost: localhost\nauth: null\n";
+        let data = Cst::parse("test.ieml", source).unwrap().to_data().unwrap();
+        let map = data.view().map().unwrap();
+        let keys = map.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>();
+        assert_eq!(keys, ["port", "host", "auth"]);
+        assert_eq!(data.to_string(), source);
+
+        let source = "auth: null\nport: 8080\nhost: localhost\n";
+        let other = Cst::parse("test.ieml", source).unwrap().to_data().unwrap();
+        assert_eq!(other.view().map().unwrap(), map);
+    }
 }
@@ -1,6 +1,7 @@
 use super::super::super::{
     node::node::StringNode,
-    mark::Mark
+    mark::Mark,
+    string_style::StringStyle,
 };
 use std::fmt::Debug;
 
@@ -20,12 +21,17 @@ impl<'data> StringView<'data> {
     }
 
     pub fn string(&self) -> &'data str {
-        self.string.as_str()
+        self.string.string.as_str()
+    }
+
+    /// Gets the form the string was written in.
+    pub fn style(&self) -> StringStyle {
+        self.string.style
     }
 }
 
 impl<'data> PartialEq for StringView<'data> {
     fn eq(&self, other: &Self) -> bool {
-        self.string.as_str() == other.string.as_str()
+        self.string.string == other.string.string
     }
 }
@@ -1,6 +1,7 @@
 use super::super::{
     super::{data::Data, mark::Mark, node::tag_node::TaggedNode},
     analyse_anchors::AnalyseAnchors,
+    options::ViewOptions,
     view::View,
 };
 use std::fmt::{self, Debug, Formatter};
@@ -11,6 +12,7 @@ pub struct TaggedView<'data, A: AnalyseAnchors<'data>> {
     node: &'data TaggedNode,
     data: &'data Data,
     anchor_analyser: A,
+    options: ViewOptions,
 }
 
 impl<'data, A: AnalyseAnchors<'data>> TaggedView<'data, A> {
@@ -19,12 +21,14 @@ impl<'data, A: AnalyseAnchors<'data>> TaggedView<'data, A> {
         node: &'data TaggedNode,
         data: &'data Data,
         anchor_analyser: A,
+        options: ViewOptions,
     ) -> Self {
         Self {
             mark,
             node,
             data,
             anchor_analyser,
+            options,
         }
     }
 
@@ -37,8 +41,12 @@ impl<'data, A: AnalyseAnchors<'data>> TaggedView<'data, A> {
     }
 
     pub fn view(&self) -> View<'data, A> {
-        let node = self.data.get(self.node.node_index);
-        View::new(node, self.data, self.anchor_analyser.clone())
+        View::new(
+            self.node.node_index,
+            self.data,
+            self.anchor_analyser.clone(),
+            self.options,
+        )
     }
 }
 
@@ -1,39 +1,102 @@
 use super::{
     super::{
+        comments::Comments,
         data::Data,
-        error::{marked, AnotherTypeError, FailedDeserializeError},
-        mark::Mark,
-        node::node::{MarkedNode, Node},
+        error::{marked, AnotherTypeError, ExpansionLimitError, FailedDeserializeError},
+        mark::{Mark, Span},
+        node::node::{MapNode, MarkedNode, Node},
+        node_id::NodeId,
+        node_path::{NodePath, PathSegment, Segment},
         node_type::NodeType,
     },
     analyse_anchors::AnalyseAnchors,
     deserialize::Deserialize,
+    options::ViewOptions,
+    select::{Select, Selector},
+    visit::{FollowAnchors, Visitor, WalkOptions, Walker},
 };
-use std::{error::Error, fmt::Debug};
+use std::{error::Error, fmt::Debug, ops::ControlFlow, path::Path};
 
 pub use super::to_match::*;
 
 #[derive(Clone, Eq)]
 pub struct View<'data, A: AnalyseAnchors<'data> = ()> {
+    index: usize,
+    segment: Option<Segment<'data>>,
     node: &'data MarkedNode,
     data: &'data Data,
     anchor_analyser: A,
+    options: ViewOptions,
 }
 
 impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
-    pub(crate) fn new(node: &'data MarkedNode, data: &'data Data, anchor_analyser: A) -> Self {
+    pub(crate) fn new(
+        index: usize,
+        data: &'data Data,
+        anchor_analyser: A,
+        options: ViewOptions,
+    ) -> Self {
         Self {
-            node,
+            index,
+            segment: None,
+            node: data.get(index),
             data,
             anchor_analyser,
+            options,
         }
     }
 
+    pub(crate) fn with_segment(mut self, segment: Segment<'data>) -> Self {
+        self.segment = Some(segment);
+        self
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Gets the handle of the node, see `Data::view_at`.
+    pub fn id(&self) -> NodeId {
+        NodeId(self.index)
+    }
+
     /// Gets the mark.
     pub fn mark(&self) -> Mark {
         self.node.mark
     }
 
+    /// Gets the span of the node in the source, if the node was read from the source.
+    pub fn span(&self) -> Option<Span> {
+        self.node.end.map(|end| Span::new(self.node.mark, end))
+    }
+
+    /// Gets the comments written around the node.
+    pub fn comments(&self) -> Option<&'data Comments> {
+        self.data.comments.get(&self.index)
+    }
+
+    /// Gets the options passed to the views obtained from this view.
+    pub fn options(&self) -> ViewOptions {
+        self.options
+    }
+
+    /// Gets the same view with other options.
+    pub fn with_options(mut self, options: ViewOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Gets the path of the file in which the node is written.
+    pub fn file_path(&self) -> &'data Path {
+        match self.data.file_index(self.index) {
+            Some(i) => match &self.data.get(i).node {
+                Node::File(file) => file.path.as_path(),
+                _ => panic!("Incorrect document structure, the node is not a File."),
+            },
+            None => Path::new(""),
+        }
+    }
+
     /// Gets the node type.
     pub fn node_type(&self) -> NodeType {
         match &self.node.node {
@@ -57,27 +120,27 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
             Node::String(i) => ToMatchView::String(StringView::new(self.node.mark, i)),
             Node::List(i) => ToMatchView::List({
                 let anchor_analyser = self.anchor_analyser.clone();
-                ListView::new(self.node.mark, i, self.data, anchor_analyser)
+                ListView::new(self.node.mark, i, self.data, anchor_analyser, self.options)
             }),
             Node::Map(i) => ToMatchView::Map({
                 let anchor_analyser = self.anchor_analyser.clone();
-                MapView::new(self.node.mark, i, self.data, anchor_analyser)
+                MapView::new(self.node.mark, i, self.data, anchor_analyser, self.options)
             }),
             Node::Tagged(i) => ToMatchView::Tagged({
                 let anchor_analyser = self.anchor_analyser.clone();
-                TaggedView::new(self.node.mark, i, self.data, anchor_analyser)
+                TaggedView::new(self.node.mark, i, self.data, anchor_analyser, self.options)
             }),
             Node::File(i) => ToMatchView::File({
                 let anchor_analyser = self.anchor_analyser.child(i.path.as_path());
-                FileView::new(self.node.mark, i, self.data, anchor_analyser)
+                FileView::new(self.node.mark, i, self.data, anchor_analyser, self.options)
             }),
             Node::TakeAnchor(i) => ToMatchView::TakeAnchor({
                 let anchor_analyser = self.anchor_analyser.clone();
-                TakeAnchorView::new(self.node.mark, i, self.data, anchor_analyser)
+                TakeAnchorView::new(self.node.mark, i, self.data, anchor_analyser, self.options)
             }),
             Node::GetAnchor(i) => ToMatchView::GetAnchor({
                 let anchor_analyser = self.anchor_analyser.clone();
-                GetAnchorView::new(self.node.mark, i, self.data, anchor_analyser)
+                GetAnchorView::new(self.node.mark, i, self.data, anchor_analyser, self.options)
             }),
         }
     }
@@ -139,9 +202,10 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
     pub fn clear_step_tagged(&self) -> Option<Self> {
         match &self.node.node {
             Node::Tagged(i) => Some(Self::new(
-                self.data.get(i.node_index),
+                i.node_index,
                 self.data,
                 self.anchor_analyser.clone(),
+                self.options,
             )),
             _ => None,
         }
@@ -151,9 +215,10 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
     pub fn clear_step_file(&self) -> Option<Self> {
         match &self.node.node {
             Node::File(i) => Some(Self::new(
-                self.data.get(i.node_index),
+                i.node_index,
                 self.data,
                 self.anchor_analyser.child(i.path.as_path()),
+                self.options,
             )),
             _ => None,
         }
@@ -163,9 +228,10 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
     pub fn clear_step_take_anchor(&self) -> Option<Self> {
         match &self.node.node {
             Node::TakeAnchor(i) => Some(Self::new(
-                self.data.get(i.node_index),
+                i.node_index,
                 self.data,
                 self.anchor_analyser.clone(),
+                self.options,
             )),
             _ => None,
         }
@@ -175,9 +241,10 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
     pub fn clear_step_get_anchor(&self) -> Option<Self> {
         match &self.node.node {
             Node::GetAnchor(i) => Some(Self::new(
-                self.data.get(i.node_index),
+                i.node_index,
                 self.data,
                 self.anchor_analyser.clone(),
+                self.options,
             )),
             _ => None,
         }
@@ -231,6 +298,77 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
         clear::<(Tagged, File, TakeAnchor), A>(self.clone())
     }
 
+    /// Gets the view of the node this node is directly under, such as the map holding it as a value
+    /// or the Tagged node holding it, `None` for the root of the document.
+    pub fn parent(&self) -> Option<Self> {
+        let index = self.data.parent(self.index)?;
+        let anchor_analyser = match &self.data.get(index).node {
+            Node::File(i) if i.node_index == self.index => self.anchor_analyser.parent(),
+            _ => None,
+        };
+        let anchor_analyser = anchor_analyser.unwrap_or_else(|| self.anchor_analyser.clone());
+        let view = Self::new(index, self.data, anchor_analyser, self.options);
+        Some(view.with_parent_segment())
+    }
+
+    /// Sets the position of the view in the node it is under.
+    pub(crate) fn with_parent_segment(self) -> Self {
+        match self.segment_in_parent() {
+            Some(segment) => self.with_segment(segment),
+            None => self,
+        }
+    }
+
+    /// Gets the key of the map entry or the index of the list item holding the node, for a value
+    /// passed to a file, the name it is passed with.
+    ///
+    /// Returns `None` if the node is directly under a node of another type, such as Tagged.
+    pub fn key_in_parent(&self) -> Option<PathSegment> {
+        self.segment_in_parent().map(PathSegment::from)
+    }
+
+    /// Iterates over the nodes the node is under, from its parent to the root of the document.
+    pub fn ancestors(&self) -> impl Iterator<Item = Self> {
+        std::iter::successors(self.parent(), |i| i.parent())
+    }
+
+    fn segment_in_parent(&self) -> Option<Segment<'data>> {
+        let parent = self.data.parent(self.index)?;
+        let find = |map: &'data MapNode| {
+            let mut entries = map.entries();
+            let (key, _) = entries.find(|(_, i)| *i == self.index)?;
+            Some(Segment::Key(key.as_str()))
+        };
+        match &self.data.get(parent).node {
+            Node::List(i) => i
+                .data
+                .iter()
+                .position(|i| *i == self.index)
+                .map(Segment::Index),
+            Node::Map(i) => find(i),
+            Node::File(i) => find(&i.file_anchors),
+            _ => None,
+        }
+    }
+
+    /// Gets the path from the view through its own Tagged and File nodes.
+    fn path(&self) -> NodePath {
+        let mut path = NodePath::new();
+        if let Some(i) = self.segment {
+            path.push(i.into());
+        }
+        let mut view = self.clone();
+        while let Some(next) = view.clear_step() {
+            match &view.node.node {
+                Node::Tagged(i) => path.push(PathSegment::Tag(i.tag.clone())),
+                Node::File(i) => path.push(PathSegment::File(i.path.clone())),
+                _ => {}
+            }
+            view = next;
+        }
+        path
+    }
+
     fn make_error<T: Error + PartialEq + Eq>(&self, error: T) -> marked::WithMarkError<T> {
         marked::WithMarkError::<T>::new(self.mark(), error)
     }
@@ -272,7 +410,13 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
         match &clear.node.node {
             Node::List(i) => Ok({
                 let anchor_analyser = self.anchor_analyser.clone();
-                ListView::new(clear.node.mark, i, clear.data, anchor_analyser)
+                ListView::new(
+                    clear.node.mark,
+                    i,
+                    clear.data,
+                    anchor_analyser,
+                    self.options,
+                )
             }),
             _ => Err(self.make_another_type_error(NodeType::List)),
         }
@@ -284,7 +428,13 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
         match &clear.node.node {
             Node::Map(i) => Ok({
                 let anchor_analyser = self.anchor_analyser.clone();
-                MapView::new(clear.node.mark, i, clear.data, anchor_analyser)
+                MapView::new(
+                    clear.node.mark,
+                    i,
+                    clear.data,
+                    anchor_analyser,
+                    self.options,
+                )
             }),
             _ => Err(self.make_another_type_error(NodeType::Map)),
         }
@@ -297,7 +447,13 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
         match &clear.node.node {
             Node::Tagged(i) => Ok({
                 let anchor_analyser = self.anchor_analyser.clone();
-                TaggedView::new(clear.node.mark, i, clear.data, anchor_analyser)
+                TaggedView::new(
+                    clear.node.mark,
+                    i,
+                    clear.data,
+                    anchor_analyser,
+                    self.options,
+                )
             }),
             _ => Err(self.make_another_type_error(NodeType::Tagged)),
         }
@@ -310,7 +466,13 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
         match &clear.node.node {
             Node::File(i) => Ok({
                 let anchor_analyser = self.anchor_analyser.child(i.path.as_path());
-                FileView::new(clear.node.mark, i, clear.data, anchor_analyser)
+                FileView::new(
+                    clear.node.mark,
+                    i,
+                    clear.data,
+                    anchor_analyser,
+                    self.options,
+                )
             }),
             _ => Err(self.make_another_type_error(NodeType::File)),
         }
@@ -323,7 +485,13 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
         match &clear.node.node {
             Node::TakeAnchor(i) => Ok({
                 let anchor_analyser = self.anchor_analyser.clone();
-                TakeAnchorView::new(clear.node.mark, i, clear.data, anchor_analyser)
+                TakeAnchorView::new(
+                    clear.node.mark,
+                    i,
+                    clear.data,
+                    anchor_analyser,
+                    self.options,
+                )
             }),
             _ => Err(self.make_another_type_error(NodeType::TakeAnchor)),
         }
@@ -336,7 +504,13 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
         match &clear.node.node {
             Node::GetAnchor(i) => Ok({
                 let anchor_analyser = self.anchor_analyser.clone();
-                GetAnchorView::new(clear.node.mark, i, clear.data, anchor_analyser)
+                GetAnchorView::new(
+                    clear.node.mark,
+                    i,
+                    clear.data,
+                    anchor_analyser,
+                    self.options,
+                )
             }),
             _ => Err(self.make_another_type_error(NodeType::GetAnchor)),
         }
@@ -353,6 +527,103 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
         }
     }
 
+    /// Gets the view at the path from this view, for example `servers[2].listen.port`.
+    ///
+    /// Tagged, File, TakeAnchor and GetAnchor nodes on the way are passed through.
+    pub fn at(&self, path: &str) -> Result<Self, marked::PathError> {
+        let path = path
+            .parse::<NodePath>()
+            .map_err(marked::PathError::InvalidPath)?;
+        let mut passed = NodePath::new();
+        let mut view = self.clone();
+        for segment in path.segments() {
+            passed.push(segment.clone());
+            view = match segment {
+                PathSegment::Key(key) => view
+                    .map()
+                    .map_err(|e| marked::PathError::ViewAnotherType(passed.clone(), e))?
+                    .get(key)
+                    .map_err(|e| marked::PathError::InvalidKey(passed.clone(), e))?,
+                PathSegment::Index(index) => view
+                    .list()
+                    .map_err(|e| marked::PathError::ViewAnotherType(passed.clone(), e))?
+                    .get(*index)
+                    .map_err(|e| marked::PathError::InvalidIndex(passed.clone(), e))?,
+                PathSegment::Tag(_) | PathSegment::File(_) => {
+                    unreachable!("A parsed path consists of keys and indexes only.")
+                }
+            };
+        }
+        Ok(view)
+    }
+
+    /// Finds the nodes matching the selector, together with their paths from this view.
+    pub fn select<'selector>(&self, selector: &'selector Selector) -> Select<'data, 'selector, A> {
+        Select::new(selector, self.clone())
+    }
+
+    /// Walks the view and the nodes under it, calling the visitor for each of them.
+    ///
+    /// When the anchors are followed, the view is first checked against the expansion limits of
+    /// its options, see `check_expansion`.
+    ///
+    /// Gives `ControlFlow::Break` if the visitor stopped the walk with `Walk::Stop`.
+    pub fn walk<V: Visitor<'data, A>>(
+        &self,
+        visitor: &mut V,
+        options: WalkOptions,
+    ) -> Result<ControlFlow<()>, marked::ExpansionLimitError> {
+        if options.follow_anchors == FollowAnchors::Always {
+            self.check_expansion()?;
+        }
+        Ok(Walker::new(visitor, options).walk(self.clone()))
+    }
+
+    /// Checks that the node with the anchors expanded stays within the limits of the options, the
+    /// document is not measured if no limit is set.
+    ///
+    /// The error is marked with the outermost reference going over the limit, or with the
+    /// innermost node going over it if the limit is reached without such a reference.
+    pub fn check_expansion(&self) -> Result<(), marked::ExpansionLimitError> {
+        let limits = self.options.limits;
+        if limits.is_unlimited() {
+            return Ok(());
+        }
+        let exceeded = |i: usize| self.data.expansion(i).exceeded(&limits);
+        let Some((limit, max)) = exceeded(self.index) else {
+            return Ok(());
+        };
+        let mut index = self.index;
+        while !matches!(self.data.get(index).node, Node::GetAnchor(_)) {
+            match self
+                .data
+                .children(index)
+                .into_iter()
+                .find(|i| exceeded(*i).is_some())
+            {
+                Some(i) => index = i,
+                None => break,
+            }
+        }
+        let error = ExpansionLimitError::new(limit, max);
+        Err(marked::ExpansionLimitError::new(
+            self.data.get(index).mark,
+            error,
+        ))
+    }
+
+    /// Decodes the view at the path from this view into type T.
+    ///
+    /// # Generic arguments
+    ///
+    /// * `T` Value type.
+    pub fn get_as<E: Error + PartialEq + Eq, T: Deserialize<'data, A, E>>(
+        &self,
+        path: &str,
+    ) -> Result<T, marked::GetAsError<E>> {
+        Ok(self.at(path)?.decode()?)
+    }
+
     /// Decodes the view into type T.
     ///
     /// # Generic arguments
@@ -361,8 +632,35 @@ impl<'data, A: AnalyseAnchors<'data>> View<'data, A> {
     pub fn decode<E: Error + PartialEq + Eq, T: Deserialize<'data, A, E>>(
         &self,
     ) -> Result<T, marked::FailedDeserializeError<E>> {
-        T::deserialize(self.clone())
-            .map_err(|e| self.make_error(FailedDeserializeError::new::<T>(Box::new(e))))
+        self.check_expansion()
+            .map_err(marked::DeserializeError::from)
+            .and_then(|_| T::deserialize(self.clone()))
+            .map_err(|e| {
+                let error = FailedDeserializeError::new::<T>(self.path(), Box::new(e));
+                self.make_error(error)
+            })
+    }
+
+    /// Decodes the view into type T, returning every failure found in the view instead of a single error tree.
+    ///
+    /// Each returned error has a single failure at the end of the chain of reasons, so its full path points
+    /// to exactly one place in the document.
+    ///
+    /// # Generic arguments
+    ///
+    /// * `T` Value type.
+    pub fn decode_all<E: Error + PartialEq + Eq, T: Deserialize<'data, A, E>>(
+        &self,
+    ) -> Result<T, Vec<marked::FailedDeserializeError<E>>> {
+        self.decode()
+            .map_err(|e: marked::FailedDeserializeError<E>| {
+                let mark = e.mark;
+                e.data
+                    .split()
+                    .into_iter()
+                    .map(|data| marked::WithMarkError::new(mark, data))
+                    .collect()
+            })
     }
 }
 
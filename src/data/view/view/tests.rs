@@ -54,7 +54,7 @@ fn make_another_type_error(
 #[test]
 fn test_null() {
     let data = test_data();
-    let view = View::new(data.get(0), &data, ());
+    let view = View::new(0, &data, (), Default::default());
     let mark = Mark { line: 2, symbol: 5 };
 
     assert_eq!(view.mark(), mark);
@@ -139,7 +139,7 @@ fn test_null() {
 #[test]
 fn test_raw() {
     let data = test_data();
-    let view = View::new(data.get(1), &data, ());
+    let view = View::new(1, &data, (), Default::default());
     let mark = Mark::default();
 
     assert_eq!(view.mark(), mark);
@@ -213,7 +213,7 @@ fn test_raw() {
 #[test]
 fn test_string() {
     let data = test_data();
-    let view = View::new(data.get(2), &data, ());
+    let view = View::new(2, &data, (), Default::default());
     let mark = Mark::default();
 
     assert_eq!(view.mark(), mark);
@@ -299,7 +299,7 @@ fn test_string() {
 #[test]
 fn test_list() {
     let data = test_data();
-    let view = View::new(data.get(3), &data, ());
+    let view = View::new(3, &data, (), Default::default());
     let mark = Mark::default();
 
     assert_eq!(view.mark(), mark);
@@ -330,7 +330,13 @@ fn test_list() {
     if let Node::List(node) = &data.get(3).node {
         assert_eq!(
             view.list(),
-            Ok(ListView::new(Default::default(), node, &data, ()))
+            Ok(ListView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a list");
@@ -384,7 +390,7 @@ fn test_list() {
 #[test]
 fn test_map() {
     let data = test_data();
-    let view = View::new(data.get(4), &data, ());
+    let view = View::new(4, &data, (), Default::default());
     let mark = Mark::default();
 
     assert_eq!(view.mark(), mark);
@@ -419,7 +425,13 @@ fn test_map() {
     if let Node::Map(node) = &data.get(4).node {
         assert_eq!(
             view.map(),
-            Ok(MapView::new(Default::default(), node, &data, ()))
+            Ok(MapView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a map");
@@ -465,7 +477,7 @@ fn test_map() {
 #[test]
 fn test_tagged() {
     let data = test_data();
-    let view = View::new(data.get(5), &data, ());
+    let view = View::new(5, &data, (), Default::default());
     let mark = Mark::default();
 
     assert_eq!(view.mark(), mark);
@@ -508,7 +520,13 @@ fn test_tagged() {
     if let Node::Map(node) = &data.get(4).node {
         assert_eq!(
             view.map(),
-            Ok(MapView::new(Default::default(), node, &data, ()))
+            Ok(MapView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a map");
@@ -516,7 +534,13 @@ fn test_tagged() {
     if let Node::Tagged(node) = &data.get(5).node {
         assert_eq!(
             view.tagged(),
-            Ok(TaggedView::new(Default::default(), node, &data, ()))
+            Ok(TaggedView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a tagged");
@@ -532,7 +556,13 @@ fn test_tagged() {
     if let Node::TakeAnchor(node) = &data.get(7).node {
         assert_eq!(
             view.take_anchor(),
-            Ok(TakeAnchorView::new(Default::default(), node, &data, ()))
+            Ok(TakeAnchorView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a take anchor");
@@ -551,7 +581,7 @@ fn test_tagged() {
 #[test]
 fn test_file() {
     let data = test_data();
-    let view = View::new(data.get(6), &data, ());
+    let view = View::new(6, &data, (), Default::default());
     let mark = Mark::default();
 
     assert_eq!(view.mark(), mark);
@@ -590,7 +620,13 @@ fn test_file() {
     if let Node::Map(node) = &data.get(4).node {
         assert_eq!(
             view.map(),
-            Ok(MapView::new(Default::default(), node, &data, ()))
+            Ok(MapView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a map");
@@ -598,7 +634,13 @@ fn test_file() {
     if let Node::Tagged(node) = &data.get(5).node {
         assert_eq!(
             view.tagged(),
-            Ok(TaggedView::new(Default::default(), node, &data, ()))
+            Ok(TaggedView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a tagged");
@@ -606,7 +648,13 @@ fn test_file() {
     if let Node::File(node) = &data.get(6).node {
         assert_eq!(
             view.file(),
-            Ok(FileView::new(Default::default(), node, &data, ()))
+            Ok(FileView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a take anchor");
@@ -614,7 +662,13 @@ fn test_file() {
     if let Node::TakeAnchor(node) = &data.get(7).node {
         assert_eq!(
             view.take_anchor(),
-            Ok(TakeAnchorView::new(Default::default(), node, &data, ()))
+            Ok(TakeAnchorView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a take anchor");
@@ -633,7 +687,7 @@ fn test_file() {
 #[test]
 fn test_take_anchor() {
     let data = test_data();
-    let view = View::new(data.get(7), &data, ());
+    let view = View::new(7, &data, (), Default::default());
     let mark = Mark::default();
 
     assert_eq!(view.mark(), mark);
@@ -676,7 +730,13 @@ fn test_take_anchor() {
     if let Node::Map(node) = &data.get(4).node {
         assert_eq!(
             view.map(),
-            Ok(MapView::new(Default::default(), node, &data, ()))
+            Ok(MapView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a map");
@@ -700,7 +760,13 @@ fn test_take_anchor() {
     if let Node::TakeAnchor(node) = &data.get(7).node {
         assert_eq!(
             view.take_anchor(),
-            Ok(TakeAnchorView::new(Default::default(), node, &data, ()))
+            Ok(TakeAnchorView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a take anchor");
@@ -719,7 +785,7 @@ fn test_take_anchor() {
 #[test]
 fn test_get_anchor() {
     let data = test_data();
-    let view = View::new(data.get(8), &data, ());
+    let view = View::new(8, &data, (), Default::default());
     let mark = Mark::default();
 
     assert_eq!(view.mark(), mark);
@@ -762,7 +828,13 @@ fn test_get_anchor() {
     if let Node::Map(node) = &data.get(4).node {
         assert_eq!(
             view.map(),
-            Ok(MapView::new(Default::default(), node, &data, ()))
+            Ok(MapView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a map");
@@ -794,10 +866,331 @@ fn test_get_anchor() {
     if let Node::GetAnchor(node) = &data.get(8).node {
         assert_eq!(
             view.get_anchor(),
-            Ok(GetAnchorView::new(Default::default(), node, &data, ()))
+            Ok(GetAnchorView::new(
+                Default::default(),
+                node,
+                &data,
+                (),
+                Default::default()
+            ))
         );
     } else {
         panic!("The node is not a get anchor");
     }
     assert_eq!(view.anchor_name(), Ok("anchor"));
 }
+
+#[derive(Debug)]
+struct Server {
+    #[allow(dead_code)]
+    port: u16,
+}
+
+impl<'data, A: AnalyseAnchors<'data>, E: Error + PartialEq + Eq> Deserialize<'data, A, E>
+    for Server
+{
+    fn deserialize(view: View<'data, A>) -> Result<Self, marked::DeserializeError<E>> {
+        let port = view.map()?.get("port")?.decode()?;
+        Ok(Self { port })
+    }
+}
+
+#[derive(Debug)]
+struct Config {
+    #[allow(dead_code)]
+    servers: Vec<Server>,
+}
+
+impl<'data, A: AnalyseAnchors<'data>, E: Error + PartialEq + Eq> Deserialize<'data, A, E>
+    for Config
+{
+    fn deserialize(view: View<'data, A>) -> Result<Self, marked::DeserializeError<E>> {
+        let servers = view.map()?.get("servers")?.decode()?;
+        Ok(Self { servers })
+    }
+}
+
+fn test_path_data() -> Data {
+    Data::new([
+        MarkedNode::new(Node::Raw("80".into()), Default::default()),
+        MarkedNode::new(Node::Raw("eighty".into()), Mark::new(4, 8)),
+        MarkedNode::new(
+            Node::Map(MapNode::new(HashMap::from([("port".to_string(), 0)]))),
+            Default::default(),
+        ),
+        MarkedNode::new(
+            Node::Map(MapNode::new(HashMap::from([("port".to_string(), 1)]))),
+            Default::default(),
+        ),
+        MarkedNode::new(
+            Node::Tagged(TaggedNode::new("server".into(), 3)),
+            Default::default(),
+        ),
+        MarkedNode::new(Node::List(ListNode::new(vec![2, 4])), Default::default()),
+        MarkedNode::new(
+            Node::Map(MapNode::new(HashMap::from([("servers".to_string(), 5)]))),
+            Default::default(),
+        ),
+    ])
+}
+
+#[test]
+fn test_decode_path() {
+    use std::convert::Infallible;
+
+    let data = test_path_data();
+    let view = data.view();
+
+    let error = view.decode::<Infallible, Config>().unwrap_err();
+    assert!(error.data.get_path().is_empty());
+    assert_eq!(
+        error.data.get_full_path().to_string(),
+        "servers[1](server).port"
+    );
+    assert!(error
+        .to_string()
+        .starts_with("0:0: servers[1](server).port: Failed to convert view to"));
+    assert!(error
+        .to_string()
+        .ends_with("\n4:8: Failed to convert view to 'u16'."));
+    assert_eq!(error.to_string().matches("port").count(), 1);
+
+    let server = view.map().unwrap().get("servers").unwrap().list().unwrap();
+    let error = server
+        .get(1)
+        .unwrap()
+        .decode::<Infallible, Server>()
+        .unwrap_err();
+    assert_eq!(error.data.get_path().to_string(), "[1](server)");
+    assert_eq!(error.data.get_full_path().to_string(), "[1](server).port");
+}
+
+#[test]
+fn test_at() {
+    use super::super::super::error::{InvalidIndexError, InvalidPathError};
+    use std::convert::Infallible;
+
+    let data = test_path_data();
+    let view = data.view();
+
+    assert_eq!(view.at("").unwrap(), view);
+    let port = view.at("servers[0].port").unwrap();
+    assert_eq!(port.raw().unwrap().raw(), "80");
+    let port = view.at("servers[1].port").unwrap();
+    assert_eq!(port.mark(), Mark::new(4, 8));
+    assert_eq!(view.get_as::<Infallible, u16>("servers[0].port"), Ok(80));
+    let error = view.get_as::<Infallible, u16>("servers[1].port");
+    assert!(matches!(error, Err(marked::GetAsError::FailedDecode(_))));
+
+    let error = view.at("servers[2].port").unwrap_err();
+    let path = "servers[2]".parse().unwrap();
+    let index_error = marked::WithMarkError::new(Mark::default(), InvalidIndexError::new(2, 2));
+    assert_eq!(error, marked::PathError::InvalidIndex(path, index_error));
+    assert_eq!(error.mark(), Some(Mark::default()));
+    assert!(error
+        .to_string()
+        .starts_with("Failed to follow the path 'servers[2]'. 0:0: "));
+
+    let error = view.at("servers[1].host").unwrap_err();
+    assert!(matches!(error, marked::PathError::InvalidKey(_, _)));
+    assert_eq!(error.path().unwrap().to_string(), "servers[1].host");
+
+    let error = view.at("servers.port").unwrap_err();
+    let type_error = make_another_type_error(NodeType::List, NodeType::Map, Mark::default());
+    let path = "servers.port".parse().unwrap();
+    assert_eq!(error, marked::PathError::ViewAnotherType(path, type_error));
+
+    let error = view.at("servers[").unwrap_err();
+    let path_error = InvalidPathError::new("servers[".into(), 7);
+    assert_eq!(error, marked::PathError::InvalidPath(path_error));
+    assert_eq!(error.path(), None);
+}
+
+#[test]
+fn test_parent() {
+    let data = test_path_data();
+    let view = data.view();
+    assert_eq!(view.parent(), None);
+    assert_eq!(view.key_in_parent(), None);
+
+    let port = view.at("servers[1].port").unwrap();
+    assert_eq!(port.key_in_parent(), Some(PathSegment::Key("port".into())));
+    let types = port.ancestors().map(|i| i.node_type()).collect::<Vec<_>>();
+    let expected = [
+        NodeType::Map,
+        NodeType::Tagged,
+        NodeType::List,
+        NodeType::Map,
+    ];
+    assert_eq!(types, expected);
+    let server = port.parent().unwrap().parent().unwrap();
+    assert_eq!(server, view.at("servers[1]").unwrap());
+    assert_eq!(server.key_in_parent(), Some(PathSegment::Index(1)));
+    assert_eq!(port.parent().unwrap().key_in_parent(), None);
+}
+
+#[test]
+fn test_parent_edit() {
+    use crate::{
+        cst::document::Cst,
+        data::{make, mark::Mark},
+    };
+    use std::convert::Infallible;
+
+    let mut data = Cst::parse("test.ieml", "a:\n\t- x\n")
+        .unwrap()
+        .to_data()
+        .unwrap();
+    let item = data.view().at("a[0]").unwrap();
+    assert_eq!(item.key_in_parent(), Some(PathSegment::Index(0)));
+
+    let mut root = data.view_mut();
+    let mut list = root.get("a").unwrap();
+    list.push(make::raw::<_, Infallible, _>(Mark::default(), (), "y"))
+        .unwrap();
+    let item = data.view().at("a[1]").unwrap();
+    let list = item.parent().unwrap();
+    assert_eq!(list.key_in_parent(), Some(PathSegment::Key("a".into())));
+}
+
+#[test]
+fn test_parent_file() {
+    use crate::{cst::document::Cst, test_dir::TestDir};
+
+    let dir = TestDir::new("parent_file");
+    std::fs::write(dir.join("include.ieml"), "value: *name\n").unwrap();
+    let source = "include: < include.ieml\n\tname: hello\n";
+    let data = Cst::parse(dir.join("main.ieml"), source)
+        .unwrap()
+        .to_data()
+        .unwrap();
+    let view = data.view();
+
+    let value = view.at("include.value").unwrap();
+    let types = value.ancestors().map(|i| i.node_type()).collect::<Vec<_>>();
+    let expected = [NodeType::Map, NodeType::File, NodeType::Map, NodeType::File];
+    assert_eq!(types, expected);
+    let file = value.parent().unwrap().parent().unwrap();
+    assert_eq!(
+        file.key_in_parent(),
+        Some(PathSegment::Key("include".into()))
+    );
+
+    let include = view.at("include").unwrap().file().unwrap();
+    let name = include.anchors().file_anchors().get("name").unwrap();
+    assert_eq!(name.key_in_parent(), Some(PathSegment::Key("name".into())));
+    assert_eq!(name.parent().unwrap().node_type(), NodeType::File);
+}
+
+#[test]
+fn test_clear_cycle() {
+    let mark = Mark::default();
+    // `&a *a` and `&b` over a list holding `*b`, which parsing rejects.
+    let data = Data::new([
+        MarkedNode::new(Node::GetAnchor(GetAnchorNode::new("a".into(), 0)), mark),
+        MarkedNode::new(Node::TakeAnchor(TakeAnchorNode::new("a".into(), 0)), mark),
+        MarkedNode::new(Node::GetAnchor(GetAnchorNode::new("b".into(), 3)), mark),
+        MarkedNode::new(Node::List(ListNode::new(vec![2])), mark),
+        MarkedNode::new(Node::TakeAnchor(TakeAnchorNode::new("b".into(), 3)), mark),
+        MarkedNode::new(Node::List(ListNode::new(vec![1, 4])), mark),
+    ]);
+    let list = data.view().list().unwrap();
+
+    let a = list.get(0).unwrap();
+    let clear = a.clear();
+    assert!(clear.is_get_anchor());
+    assert_eq!(clear.index(), 0);
+    assert!(a.clear_tag().is_get_anchor());
+
+    let b = list.get(1).unwrap().clear();
+    assert!(b.is_list());
+    let item = b.list().unwrap().get(0).unwrap();
+    assert_eq!(item.clear(), b);
+
+    assert_eq!(data.view(), data.view());
+    assert_eq!(data, data.clone());
+    assert!(format!("{:?}", data).contains("GetAnchorView"));
+}
+
+#[test]
+fn test_expansion_limits() {
+    use super::super::{
+        options::ExpansionLimits,
+        visit::{FollowAnchors, Order, Visitor, Walk},
+    };
+    use crate::{
+        cst::document::Cst,
+        data::error::{ExpansionLimit, ExpansionLimitError},
+    };
+    use std::convert::Infallible;
+
+    struct Counter(usize);
+
+    impl<'data> Visitor<'data, ()> for Counter {
+        fn visit_null(&mut self, _: &NullView) -> Walk {
+            self.0 += 1;
+            Default::default()
+        }
+    }
+
+    let source = "a: &a
+	- 1
+	- 1
+b: &b
+	- *a
+	- *a
+c:
+	- *b
+	- *b
+";
+    let data = Cst::parse("test.ieml", source).unwrap().to_data().unwrap();
+    let c = data.view().at("c").unwrap();
+    let decoded = c.decode::<Infallible, Vec<Vec<Vec<u8>>>>().unwrap();
+    assert_eq!(decoded, vec![vec![vec![1, 1]; 2]; 2]);
+
+    let limited = |max_nodes, max_dereferences, max_depth| {
+        let limits = ExpansionLimits::new(max_nodes, max_dereferences, max_depth);
+        c.clone()
+            .with_options(ViewOptions::default().with_limits(limits))
+    };
+    assert_eq!(
+        limited(Some(20), Some(6), Some(5)).check_expansion(),
+        Ok(())
+    );
+
+    let error = |limit, max, mark| {
+        let error = ExpansionLimitError::new(limit, max);
+        Err(marked::ExpansionLimitError::new(mark, error))
+    };
+    let view = limited(Some(9), None, None);
+    let expected = error(ExpansionLimit::Nodes, 9, Mark::new(7, 3));
+    assert_eq!(view.check_expansion(), expected);
+    let view = limited(Some(19), None, None);
+    let expected = error(ExpansionLimit::Nodes, 19, Mark::new(7, 1));
+    assert_eq!(view.check_expansion(), expected);
+    let view = limited(None, Some(2), None);
+    let expected = error(ExpansionLimit::Dereferences, 2, Mark::new(7, 3));
+    assert_eq!(view.check_expansion(), expected);
+    let view = limited(None, None, Some(4));
+    let expected = error(ExpansionLimit::Depth, 4, Mark::new(7, 3));
+    assert_eq!(view.check_expansion(), expected);
+
+    let view = limited(Some(10), None, None);
+    let error = view.decode::<Infallible, Vec<Vec<Vec<u8>>>>().unwrap_err();
+    let reason = error.data.get_reason();
+    assert!(matches!(
+        **reason,
+        marked::DeserializeError::ExpansionLimit(_)
+    ));
+    let item = view.list().unwrap().get(0).unwrap();
+    assert_eq!(
+        item.decode::<Infallible, Vec<Vec<u8>>>(),
+        Ok(vec![vec![1, 1]; 2])
+    );
+
+    let options = WalkOptions::new(Order::Pre, FollowAnchors::Always);
+    let mut counter = Counter(0);
+    assert!(view.walk(&mut counter, options).is_err());
+    let options = WalkOptions::new(Order::Pre, FollowAnchors::Never);
+    assert_eq!(view.walk(&mut counter, options), Ok(ControlFlow::Continue(())));
+}
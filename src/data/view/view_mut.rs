@@ -0,0 +1,754 @@
+use super::{
+    super::{
+        data::Data,
+        error::{marked, AnotherTypeError, InvalidIndexError, InvalidKeyError},
+        make::{
+            error::{
+                marked::{MakeError, MakeResult},
+                MakeErrorReason,
+            },
+            init::{check_cycles, init_step},
+            Maker,
+        },
+        mark::Mark,
+        node::node::{MapNode, MarkedNode, Node, TaggedNode, TakeAnchorNode},
+        node_id::NodeId,
+        node_type::NodeType,
+        value::Value,
+    },
+    view::View,
+};
+use std::{
+    collections::HashSet,
+    convert::Infallible,
+    error::Error,
+    fmt::{self, Debug, Formatter},
+    path::PathBuf,
+};
+
+type EditResult<T, E> = Result<T, marked::EditError<E>>;
+
+/// View of a node that allows editing the document.
+///
+/// Edits keep the document consistent: anchors taken by added nodes are registered in the file
+/// they are written in and references to anchors are resolved, removing a node whose anchor is
+/// still referenced elsewhere fails, and included files keep their parent files.
+pub struct ViewMut<'data> {
+    index: usize,
+    data: &'data mut Data,
+}
+
+impl<'data> ViewMut<'data> {
+    pub(crate) fn new(index: usize, data: &'data mut Data) -> Self {
+        Self { index, data }
+    }
+
+    /// Gets the view for reading the node.
+    pub fn view(&self) -> View<'_> {
+        View::new(self.index, self.data, (), Default::default())
+    }
+
+    /// Gets the handle of the node, see `Data::view_at`.
+    pub fn id(&self) -> NodeId {
+        NodeId(self.index)
+    }
+
+    pub fn mark(&self) -> Mark {
+        self.data.get(self.index).mark
+    }
+
+    pub fn node_type(&self) -> NodeType {
+        self.view().node_type()
+    }
+
+    fn inner_index(&self) -> usize {
+        let mut index = self.index;
+        loop {
+            index = match &self.data.get(index).node {
+                Node::Tagged(i) => i.node_index,
+                Node::TakeAnchor(i) => i.node_index,
+                Node::File(i) => i.node_index,
+                _ => return index,
+            }
+        }
+    }
+
+    /// Gets the node under the tags, the take anchors and the files.
+    ///
+    /// The node a `GetAnchor` refers to is not reached, it is edited where the anchor is taken.
+    pub fn inner(&mut self) -> ViewMut<'_> {
+        ViewMut::new(self.inner_index(), self.data)
+    }
+
+    fn another_type_error(&self, index: usize, requested: NodeType) -> marked::AnotherTypeError {
+        let node_type = View::new(index, self.data, (), Default::default()).node_type();
+        let error = AnotherTypeError::new(requested, node_type);
+        marked::WithMarkError::new(self.data.get(index).mark, error)
+    }
+
+    fn map_index(&self) -> Result<usize, marked::AnotherTypeError> {
+        let index = self.inner_index();
+        match &self.data.get(index).node {
+            Node::Map(_) => Ok(index),
+            _ => Err(self.another_type_error(index, NodeType::Map)),
+        }
+    }
+
+    fn list_index(&self) -> Result<usize, marked::AnotherTypeError> {
+        let index = self.inner_index();
+        match &self.data.get(index).node {
+            Node::List(_) => Ok(index),
+            _ => Err(self.another_type_error(index, NodeType::List)),
+        }
+    }
+
+    fn map_mut(&mut self, index: usize) -> &mut MapNode {
+        match &mut self.data.get_mut(index).node {
+            Node::Map(i) => i,
+            _ => panic!("Incorrect document structure, the node is not a Map."),
+        }
+    }
+
+    fn list_mut(&mut self, index: usize) -> &mut Vec<usize> {
+        match &mut self.data.get_mut(index).node {
+            Node::List(i) => &mut i.data,
+            _ => panic!("Incorrect document structure, the node is not a List."),
+        }
+    }
+
+    fn map_get(&self, index: usize, key: &str) -> Option<usize> {
+        match &self.data.get(index).node {
+            Node::Map(i) => i.get(key),
+            _ => None,
+        }
+    }
+
+    fn list_len(&self, index: usize) -> usize {
+        match &self.data.get(index).node {
+            Node::List(i) => i.data.len(),
+            _ => 0,
+        }
+    }
+
+    /// Gets the value of the map entry by key, looking through tags, take anchors and files.
+    pub fn get(&mut self, key: &str) -> Result<ViewMut<'_>, marked::MapError> {
+        let index = self.map_index()?;
+        match self.map_get(index, key) {
+            Some(i) => Ok(ViewMut::new(i, self.data)),
+            None => {
+                let error = InvalidKeyError::new(key.into());
+                Err(marked::WithMarkError::new(self.data.get(index).mark, error).into())
+            }
+        }
+    }
+
+    /// Gets the item of the list by index, looking through tags, take anchors and files.
+    pub fn get_index(&mut self, index: usize) -> Result<ViewMut<'_>, marked::ListError> {
+        let list = self.list_index()?;
+        let len = self.list_len(list);
+        match index < len {
+            true => Ok(ViewMut::new(self.list_mut(list)[index], self.data)),
+            false => Err(self.invalid_index_error(list, index, len).into()),
+        }
+    }
+
+    fn invalid_index_error(
+        &self,
+        list: usize,
+        index: usize,
+        len: usize,
+    ) -> marked::InvalidIndexError {
+        let error = InvalidIndexError::new(index, len);
+        marked::WithMarkError::new(self.data.get(list).mark, error)
+    }
+
+    fn file_path(&self, file: Option<usize>) -> PathBuf {
+        match file.map(|i| &self.data.get(i).node) {
+            Some(Node::File(i)) => i.path.clone(),
+            _ => PathBuf::new(),
+        }
+    }
+
+    fn file_anchors(&mut self, file: usize) -> &mut MapNode {
+        match &mut self.data.get_mut(file).node {
+            Node::File(i) => &mut i.anchors,
+            _ => panic!("Incorrect document structure, the node is not a File."),
+        }
+    }
+
+    /// Checks that no `GetAnchor` outside the removed nodes refers to one of them.
+    fn check_removed<E: Error + PartialEq + Eq>(
+        &self,
+        removed: &HashSet<usize>,
+    ) -> Result<(), MakeError<E>> {
+        let root = self.data.root();
+        for i in self.data.descendants(root).difference(removed) {
+            if let Node::GetAnchor(anchor) = &self.data.get(*i).node {
+                if removed.contains(&anchor.node_index) {
+                    let path = self.file_path(self.data.file_index(*i));
+                    let reason = MakeErrorReason::AnchorInUse(anchor.name.clone());
+                    return Err(MakeError::new_with(self.data.get(*i).mark, path, reason));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the anchors taken on the removed nodes from the file.
+    fn forget_anchors(&mut self, file: Option<usize>, removed: &HashSet<usize>) {
+        if let Some(file) = file {
+            let anchors = self.file_anchors(file);
+            anchors.retain(|_, i| !removed.contains(&i));
+        }
+    }
+
+    /// Adds the nodes made by `f` after the existing ones.
+    ///
+    /// Returns the output of `f`, the index of the made node and the anchors taken in it.
+    fn make<O, E, F>(
+        &mut self,
+        file: Option<usize>,
+        f: F,
+    ) -> Result<(O, usize, MapNode), MakeError<E>>
+    where
+        E: Error + PartialEq + Eq,
+        F: FnOnce(&mut Maker) -> MakeResult<O, E>,
+    {
+        let len = self.data.data.len();
+        let path = self.file_path(file);
+        let mut maker = Maker::new(self.data, path);
+        match f(&mut maker) {
+            Ok(output) => {
+                let anchors = std::mem::take(maker.anchors());
+                Ok((output, maker.last(), anchors))
+            }
+            Err(error) => {
+                self.data.truncate(len);
+                Err(error)
+            }
+        }
+    }
+
+    /// Registers the anchors taken in the made node placed at `index` and resolves the
+    /// references to anchors in it, then moves the root back to the end.
+    ///
+    /// A document whose root is not a `File` has no anchors, so the made node must neither take
+    /// nor refer to any.
+    ///
+    /// Fails if the made node makes an anchor refer to itself through the nodes it holds.
+    ///
+    /// On failure `undo` takes the made node out of its place and the made nodes are dropped.
+    fn commit<E, U>(
+        &mut self,
+        file: Option<usize>,
+        removed: &HashSet<usize>,
+        anchors: MapNode,
+        index: usize,
+        len: usize,
+        undo: U,
+    ) -> Result<(), MakeError<E>>
+    where
+        E: Error + PartialEq + Eq,
+        U: FnOnce(&mut Data),
+    {
+        let root = len - 1;
+        let Some(file) = file else {
+            let mut made = std::iter::once(index).chain(len..self.data.data.len());
+            let error = made.find_map(|i| {
+                let node = self.data.get(i);
+                let reason = match &node.node {
+                    Node::TakeAnchor(node) => MakeErrorReason::AnchorWithoutFile(node.name.clone()),
+                    Node::GetAnchor(node) => MakeErrorReason::AnchorDoesntExist(node.name.clone()),
+                    _ => return None,
+                };
+                Some(MakeError::new_with(node.mark, PathBuf::new(), reason))
+            });
+            return match error {
+                Some(error) => {
+                    undo(self.data);
+                    self.data.truncate(len);
+                    Err(error)
+                }
+                None => {
+                    self.data.move_root(root);
+                    Ok(())
+                }
+            };
+        };
+        let saved = self.file_anchors(file).clone();
+        self.forget_anchors(Some(file), removed);
+        let mut result = Ok(());
+        for (name, i) in anchors.entries() {
+            if self.file_anchors(file).contains_key(name) {
+                let path = self.file_path(Some(file));
+                let reason = MakeErrorReason::AnchorAlreadyExist(name.clone());
+                result = Err(MakeError::new_with(self.data.get(i).mark, path, reason));
+                break;
+            }
+            self.file_anchors(file).insert(name.clone(), i);
+        }
+        let result = result
+            .and_then(|_| init_step(self.data, file, index))
+            .and_then(|_| check_cycles(self.data, root));
+        match &result {
+            Ok(()) => self.data.move_root(root),
+            Err(_) => {
+                *self.file_anchors(file) = saved;
+                undo(self.data);
+                self.data.truncate(len);
+            }
+        }
+        result
+    }
+
+    /// Replaces the node with the one made by `f`, keeping the comments of the node unless `f`
+    /// attaches its own.
+    ///
+    /// Anchors referring to the node refer to the new one afterwards. Fails if a node under the
+    /// replaced one is referred to by a `GetAnchor` outside of it.
+    pub fn replace<O, E, F>(&mut self, f: F) -> EditResult<O, E>
+    where
+        E: Error + PartialEq + Eq,
+        F: FnOnce(&mut Maker) -> MakeResult<O, E>,
+    {
+        let index = self.index;
+        let mut removed = self.data.descendants(index);
+        removed.remove(&index);
+        self.check_removed(&removed)?;
+
+        let file = self.data.file_index(index);
+        let len = self.data.data.len();
+        let (output, made, anchors) = self.make(file, f)?;
+        let node = std::mem::take(self.data.get_mut(made));
+        let old = std::mem::replace(self.data.get_mut(index), node);
+        let old_comments = self.data.comments.get(&index).cloned();
+        if let Some(comments) = self.data.comments.remove(&made) {
+            self.data.comments.insert(index, comments);
+        }
+        self.commit(file, &removed, anchors, index, len, |data| {
+            *data.get_mut(index) = old;
+            match old_comments {
+                Some(comments) => data.comments.insert(index, comments),
+                None => data.comments.remove(&index),
+            };
+        })?;
+        Ok(output)
+    }
+
+    /// Replaces the node with the value, keeping the mark and the comments of the node.
+    pub fn set<V: Into<Value>>(&mut self, value: V) -> EditResult<(), Infallible> {
+        let value = value.into();
+        let mark = self.mark();
+        self.replace(value.make(mark))
+    }
+
+    /// Sets the value of the map entry, the entry is added if the map does not have the key.
+    pub fn insert<O, E, F>(&mut self, key: &str, f: F) -> EditResult<O, E>
+    where
+        E: Error + PartialEq + Eq,
+        F: FnOnce(&mut Maker) -> MakeResult<O, E>,
+    {
+        let map = self.map_index()?;
+        if let Some(i) = self.map_get(map, key) {
+            return ViewMut::new(i, self.data).replace(f);
+        }
+        let file = self.data.file_index(map);
+        let len = self.data.data.len();
+        let (output, made, anchors) = self.make(file, f)?;
+        self.map_mut(map).insert(key.into(), made);
+        self.commit(file, &HashSet::new(), anchors, made, len, |data| {
+            if let Node::Map(i) = &mut data.get_mut(map).node {
+                i.remove(key);
+            }
+        })?;
+        Ok(output)
+    }
+
+    /// Removes the map entry, returns whether the map had the key.
+    ///
+    /// Fails if the value of the entry or a node under it is referred to by a `GetAnchor`
+    /// outside of it.
+    pub fn remove(&mut self, key: &str) -> EditResult<bool, Infallible> {
+        let map = self.map_index()?;
+        let Some(index) = self.map_get(map, key) else {
+            return Ok(false);
+        };
+        let removed = self.data.descendants(index);
+        self.check_removed(&removed)?;
+        self.forget_anchors(self.data.file_index(map), &removed);
+        self.map_mut(map).remove(key);
+        Ok(true)
+    }
+
+    /// Inserts the item made by `f` into the list at the position.
+    pub fn insert_index<O, E, F>(&mut self, index: usize, f: F) -> EditResult<O, E>
+    where
+        E: Error + PartialEq + Eq,
+        F: FnOnce(&mut Maker) -> MakeResult<O, E>,
+    {
+        let list = self.list_index()?;
+        let len = self.list_len(list);
+        if index > len {
+            return Err(self.invalid_index_error(list, index, len).into());
+        }
+        let file = self.data.file_index(list);
+        let len = self.data.data.len();
+        let (output, made, anchors) = self.make(file, f)?;
+        self.list_mut(list).insert(index, made);
+        self.commit(file, &HashSet::new(), anchors, made, len, |data| {
+            if let Node::List(i) = &mut data.get_mut(list).node {
+                i.data.remove(index);
+            }
+        })?;
+        Ok(output)
+    }
+
+    /// Adds the item made by `f` to the end of the list.
+    pub fn push<O, E, F>(&mut self, f: F) -> EditResult<O, E>
+    where
+        E: Error + PartialEq + Eq,
+        F: FnOnce(&mut Maker) -> MakeResult<O, E>,
+    {
+        let list = self.list_index()?;
+        let len = self.list_len(list);
+        self.insert_index(len, f)
+    }
+
+    /// Removes the item of the list.
+    ///
+    /// Fails if the item or a node under it is referred to by a `GetAnchor` outside of it.
+    pub fn remove_index(&mut self, index: usize) -> EditResult<(), Infallible> {
+        let list = self.list_index()?;
+        let len = self.list_len(list);
+        if index >= len {
+            return Err(self.invalid_index_error(list, index, len).into());
+        }
+        let item = self.list_mut(list)[index];
+        let removed = self.data.descendants(item);
+        self.check_removed(&removed)?;
+        self.forget_anchors(self.data.file_index(list), &removed);
+        self.list_mut(list).remove(index);
+        Ok(())
+    }
+
+    /// Puts the node under a new node made by `f` from the index of the node, the view refers to
+    /// the new node afterwards.
+    ///
+    /// The node keeps its index, so its handle stays valid, and the references to the node refer
+    /// to the new node. Returns the index of the node.
+    fn wrap<F: FnOnce(usize) -> Node>(&mut self, f: F) -> usize {
+        let root = self.data.root();
+        let index = self.index;
+        let wrapper = self.data.push(MarkedNode::new(f(index), self.mark()));
+        self.data.redirect(index, wrapper);
+        if let Some(comments) = self.data.comments.remove(&index) {
+            self.data.comments.insert(wrapper, comments);
+        }
+        if index != root {
+            self.data.move_root(root);
+        }
+        self.index = wrapper;
+        index
+    }
+
+    /// Puts the node under a tag, the comments of the node stay with the tag.
+    pub fn wrap_tag<S: Into<String>>(&mut self, tag: S) {
+        let tag = tag.into();
+        self.wrap(|i| Node::Tagged(TaggedNode::new(tag, i)));
+    }
+
+    /// Puts the node under a take anchor, the anchor is taken in the file the node is written in.
+    pub fn wrap_anchor<S: Into<String>>(&mut self, name: S) -> EditResult<(), Infallible> {
+        let name = name.into();
+        let Some(file) = self.data.file_index(self.index) else {
+            let reason = MakeErrorReason::AnchorWithoutFile(name);
+            return Err(MakeError::new_with(self.mark(), PathBuf::new(), reason).into());
+        };
+        if self.file_anchors(file).contains_key(&name) {
+            let path = self.file_path(Some(file));
+            let reason = MakeErrorReason::AnchorAlreadyExist(name);
+            return Err(MakeError::new_with(self.mark(), path, reason).into());
+        }
+        let node_name = name.clone();
+        let moved = self.wrap(|i| Node::TakeAnchor(TakeAnchorNode::new(node_name, i)));
+        if let Some(file) = self.data.file_index(self.index) {
+            self.file_anchors(file).insert(name, moved);
+        }
+        Ok(())
+    }
+}
+
+impl Debug for ViewMut<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.view())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::make;
+    use super::*;
+    use crate::{cst::document::Cst, test_dir::TestDir};
+
+    const SOURCE: &str = "port: 8080
+motd: &motd > hello
+copy: *motd
+list:
+	- a
+	- b
+";
+
+    fn data() -> Data {
+        Cst::parse("test.ieml", SOURCE).unwrap().to_data().unwrap()
+    }
+
+    #[test]
+    fn test_set() {
+        let mut data = data();
+        let mut root = data.view_mut();
+        root.get("port")
+            .unwrap()
+            .set(Value::Raw("9090".into()))
+            .unwrap();
+        root.get("motd").unwrap().inner().set("bye").unwrap();
+        assert!(root.get_index(0).is_err());
+        let copy = data.view().map().unwrap().get("copy").unwrap();
+        let copy = copy.clear_step_get_anchor().unwrap();
+        assert_eq!(copy.string().unwrap().string(), "bye");
+        let expected = "port: 9090\nmotd: &motd > bye\ncopy: *motd\nlist:\n\t- a\n\t- b\n";
+        assert_eq!(data.to_string(), expected);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut data = data();
+        let mut root = data.view_mut();
+        root.insert(
+            "name",
+            make::string::<_, Infallible, _>(Mark::default(), (), "main"),
+        )
+        .unwrap();
+        let mut list = root.get("list").unwrap();
+        list.push(make::raw::<_, Infallible, _>(Mark::default(), (), "c"))
+            .unwrap();
+        list.insert_index(0, make::null::<_, Infallible>(Mark::default(), ()))
+            .unwrap();
+        list.remove_index(1).unwrap();
+        let error = InvalidIndexError::new(5, 3);
+        assert_eq!(
+            list.remove_index(5),
+            Err(marked::WithMarkError::new(Mark::new(4, 1), error).into())
+        );
+
+        let error = MakeError::new_with(
+            Mark::new(2, 6),
+            "test.ieml",
+            MakeErrorReason::AnchorInUse("motd".into()),
+        );
+        assert_eq!(root.remove("motd"), Err(error.into()));
+        assert_eq!(root.remove("copy"), Ok(true));
+        assert_eq!(root.remove("copy"), Ok(false));
+        assert_eq!(root.remove("motd"), Ok(true));
+
+        let expected = "port: 8080\nlist:\n\t- null\n\t- b\n\t- c\nname: > main\n";
+        assert_eq!(data.to_string(), expected);
+        assert!(data.view().is_file());
+        let file = data.view().file().unwrap();
+        assert_eq!(file.anchors().anchors().len(), 0);
+    }
+
+    #[test]
+    fn test_anchors() {
+        let mut data = data();
+        let before = data.to_string();
+        let mut root = data.view_mut();
+        let missing = make::get_anchor::<_, Infallible, _>(Mark::new(9, 0), (), "missing");
+        let error = MakeError::new_with(
+            Mark::new(9, 0),
+            "test.ieml",
+            MakeErrorReason::AnchorDoesntExist("missing".into()),
+        );
+        assert_eq!(root.insert("copy", missing), Err(error.into()));
+        let twice = make::take_anchor::<_, Infallible, _, _>(
+            Mark::new(9, 0),
+            "motd",
+            make::null(Mark::new(9, 0), ()),
+        );
+        let error = MakeError::new_with(
+            Mark::new(9, 0),
+            "test.ieml",
+            MakeErrorReason::AnchorAlreadyExist("motd".into()),
+        );
+        assert_eq!(root.insert("other", twice), Err(error.into()));
+        assert_eq!(data.to_string(), before);
+
+        let mut root = data.view_mut();
+        let mut list = root.get("list").unwrap();
+        list.get_index(1).unwrap().wrap_anchor("item").unwrap();
+        let error = MakeError::new_with(
+            Mark::new(5, 3),
+            "test.ieml",
+            MakeErrorReason::AnchorAlreadyExist("item".into()),
+        );
+        let mut item = list.get_index(1).unwrap();
+        assert_eq!(item.wrap_anchor("item"), Err(error.into()));
+        item.wrap_tag("letter");
+        root.get("port").unwrap().wrap_tag("number");
+        root.insert(
+            "last",
+            make::get_anchor::<_, Infallible, _>(Mark::default(), (), "item"),
+        )
+        .unwrap();
+
+        let expected = "port: = number: 8080
+motd: &motd > hello
+copy: *motd
+list:
+	- a
+	- = letter: &item b
+last: *item
+";
+        assert_eq!(data.to_string(), expected);
+        let view = data.view().map().unwrap().get("last").unwrap();
+        let target = view.clear_step_get_anchor().unwrap();
+        assert_eq!(target.raw().unwrap().raw(), "b");
+    }
+
+    #[test]
+    fn test_anchor_cycle() {
+        let mut data = Cst::parse("test.ieml", "a: &a\n\t- x\n")
+            .unwrap()
+            .to_data()
+            .unwrap();
+        let before = data.clone();
+        let mut root = data.view_mut();
+        let copy = make::get_anchor::<_, Infallible, _>(Mark::new(9, 0), (), "a");
+        let error = MakeError::new_with(
+            Mark::new(9, 0),
+            "test.ieml",
+            MakeErrorReason::AnchorCycle(vec!["a".into(), "a".into()]),
+        );
+        assert_eq!(root.get("a").unwrap().inner().push(copy), Err(error.into()));
+        assert_eq!(data, before);
+        assert_eq!(data.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_ids() {
+        let mut data = data();
+        let root = data.view().id();
+        let port = data.view().at("port").unwrap().id();
+        let item = data.view().at("list[0]").unwrap().id();
+        let parent = |data: &Data, id| {
+            let view = data.view_at(id).unwrap();
+            view.ancestors().next().unwrap().node_type()
+        };
+
+        data.view_mut()
+            .insert(
+                "name",
+                make::raw::<_, Infallible, _>(Mark::default(), (), "main"),
+            )
+            .unwrap();
+        assert_eq!(data.view_at(port).unwrap().raw().unwrap().raw(), "8080");
+        assert_eq!(data.view_at(item).unwrap().raw().unwrap().raw(), "a");
+        assert_eq!(data.view_at(root), None);
+        assert_eq!(data.view_at(data.view().id()), Some(data.view()));
+
+        let mut root = data.view_mut();
+        let mut port_view = root.get("port").unwrap();
+        port_view.wrap_tag("number");
+        assert_eq!(port_view.id(), data.view().at("port").unwrap().id());
+        let mut root = data.view_mut();
+        let mut list = root.get("list").unwrap();
+        list.get_index(0).unwrap().wrap_anchor("first").unwrap();
+        assert_eq!(data.view_at(port).unwrap().raw().unwrap().raw(), "8080");
+        assert_eq!(parent(&data, port), NodeType::Tagged);
+        assert_eq!(data.view_at(item).unwrap().raw().unwrap().raw(), "a");
+        assert_eq!(parent(&data, item), NodeType::TakeAnchor);
+        let expected = "port: = number: 8080
+motd: &motd > hello
+copy: *motd
+list:
+	- &first a
+	- b
+name: main
+";
+        assert_eq!(data.to_string(), expected);
+    }
+
+    #[test]
+    fn test_anchors_without_file() {
+        let mut data = Data::new([
+            MarkedNode::new(Node::Raw("8080".into()), Default::default()),
+            MarkedNode::new(
+                Node::Map(MapNode::new([("port".to_string(), 0)])),
+                Default::default(),
+            ),
+        ]);
+        let mut root = data.view_mut();
+        let anchor = make::take_anchor::<_, Infallible, _, _>(
+            Mark::new(1, 0),
+            "name",
+            make::null(Mark::new(1, 0), ()),
+        );
+        let error = MakeError::new_with(
+            Mark::new(1, 0),
+            "",
+            MakeErrorReason::AnchorWithoutFile("name".into()),
+        );
+        assert_eq!(root.insert("name", anchor), Err(error.into()));
+        let copy = make::get_anchor::<_, Infallible, _>(Mark::new(2, 0), (), "name");
+        let error = MakeError::new_with(
+            Mark::new(2, 0),
+            "",
+            MakeErrorReason::AnchorDoesntExist("name".into()),
+        );
+        assert_eq!(root.get("port").unwrap().replace(copy), Err(error.into()));
+        let error = MakeError::new_with(
+            Mark::default(),
+            "",
+            MakeErrorReason::AnchorWithoutFile("port".into()),
+        );
+        let mut port = root.get("port").unwrap();
+        assert_eq!(port.wrap_anchor("port"), Err(error.into()));
+        assert_eq!(data.to_string(), "port: 8080\n");
+
+        let mut root = data.view_mut();
+        root.insert(
+            "name",
+            make::raw::<_, Infallible, _>(Mark::default(), (), "main"),
+        )
+        .unwrap();
+        assert_eq!(data.to_string(), "port: 8080\nname: main\n");
+    }
+
+    #[test]
+    fn test_file_parent() {
+        let dir = TestDir::new("view_mut");
+        std::fs::write(dir.join("include.ieml"), "value: *name\n").unwrap();
+        let source = "name: &name > main\ninclude: < include.ieml\n";
+        let mut data = Cst::parse(dir.join("main.ieml"), source)
+            .unwrap()
+            .to_data()
+            .unwrap();
+        let mut root = data.view_mut();
+        root.insert(
+            "port",
+            make::raw::<_, Infallible, _>(Mark::default(), (), "80"),
+        )
+        .unwrap();
+        root.get("include").unwrap().wrap_tag("shared");
+
+        let include = data.view().map().unwrap().get("include").unwrap();
+        let file = include.tagged().unwrap().view().file().unwrap();
+        let parent = file.anchors().parent().unwrap();
+        assert_eq!(
+            parent.get("name").unwrap().string().unwrap().string(),
+            "main"
+        );
+        let value = file.view().map().unwrap().get("value").unwrap();
+        let value = value.clear_step_get_anchor().unwrap();
+        assert_eq!(value.string().unwrap().string(), "main");
+    }
+}
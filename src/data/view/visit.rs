@@ -0,0 +1,373 @@
+use super::{
+    analyse_anchors::AnalyseAnchors,
+    view::{
+        FileView, GetAnchorView, ListView, MapView, NullView, RawView, StringView, TaggedView,
+        TakeAnchorView, ToMatchView, View,
+    },
+};
+use std::{marker::PhantomData, ops::ControlFlow};
+
+/// What the walk does after a call of the visitor.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Walk {
+    #[default]
+    Continue,
+    /// Does not walk the nodes under the node, has no effect in the post order.
+    SkipChildren,
+    /// Ends the walk.
+    Stop,
+}
+
+/// When the visitor is called for a node.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Order {
+    /// Before the nodes under it.
+    #[default]
+    Pre,
+    /// After the nodes under it.
+    Post,
+}
+
+/// Whether the walk goes into the nodes that GetAnchor nodes refer to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FollowAnchors {
+    #[default]
+    Never,
+    /// Follows every GetAnchor except one referring to a node that is being walked already, so
+    /// cyclic references end.
+    Always,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct WalkOptions {
+    pub order: Order,
+    pub follow_anchors: FollowAnchors,
+}
+
+impl WalkOptions {
+    pub fn new(order: Order, follow_anchors: FollowAnchors) -> Self {
+        Self {
+            order,
+            follow_anchors,
+        }
+    }
+}
+
+/// Callbacks for the nodes of the document met by `View::walk`.
+///
+/// The entries of a map and the items of a list are visited before the nodes they hold, the
+/// anchors passed to a file are visited as map entries before the contents of the file.
+#[allow(unused_variables)]
+pub trait Visitor<'data, A: AnalyseAnchors<'data>> {
+    fn visit_null(&mut self, null: &NullView) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_raw(&mut self, raw: &RawView<'data>) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_string(&mut self, string: &StringView<'data>) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_list(&mut self, list: &ListView<'data, A>) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_list_item(&mut self, index: usize, view: &View<'data, A>) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_map(&mut self, map: &MapView<'data, A>) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_map_entry(&mut self, key: &'data str, view: &View<'data, A>) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_tagged(&mut self, tagged: &TaggedView<'data, A>) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_file(&mut self, file: &FileView<'data, A>) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_take_anchor(&mut self, take_anchor: &TakeAnchorView<'data, A>) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_get_anchor(&mut self, get_anchor: &GetAnchorView<'data, A>) -> Walk {
+        Walk::Continue
+    }
+}
+
+pub(crate) struct Walker<'visitor, A, V> {
+    visitor: &'visitor mut V,
+    options: WalkOptions,
+    /// Nodes being walked, from the start of the walk to the current node.
+    stack: Vec<usize>,
+    anchor_analyser: PhantomData<A>,
+}
+
+impl<'visitor, 'data, A, V> Walker<'visitor, A, V>
+where
+    A: AnalyseAnchors<'data>,
+    V: Visitor<'data, A>,
+{
+    pub(crate) fn new(visitor: &'visitor mut V, options: WalkOptions) -> Self {
+        Self {
+            visitor,
+            options,
+            stack: Vec::new(),
+            anchor_analyser: PhantomData,
+        }
+    }
+
+    /// Calls the visitor for the node and walks the nodes under it in the requested order.
+    fn node<F, C>(&mut self, visit: F, children: C) -> ControlFlow<()>
+    where
+        F: FnOnce(&mut V) -> Walk,
+        C: FnOnce(&mut Self) -> ControlFlow<()>,
+    {
+        match self.options.order {
+            Order::Pre => match visit(self.visitor) {
+                Walk::Continue => children(self),
+                Walk::SkipChildren => ControlFlow::Continue(()),
+                Walk::Stop => ControlFlow::Break(()),
+            },
+            Order::Post => {
+                children(self)?;
+                match visit(self.visitor) {
+                    Walk::Stop => ControlFlow::Break(()),
+                    _ => ControlFlow::Continue(()),
+                }
+            }
+        }
+    }
+
+    fn entries(&mut self, map: &MapView<'data, A>) -> ControlFlow<()> {
+        for (key, i) in map.iter() {
+            self.node(|v| v.visit_map_entry(key, &i), |w| w.walk(i.clone()))?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    pub(crate) fn walk(&mut self, view: View<'data, A>) -> ControlFlow<()> {
+        self.stack.push(view.index());
+        let result = match view.to_match() {
+            ToMatchView::Null(i) => self.node(|v| v.visit_null(&i), |_| ControlFlow::Continue(())),
+            ToMatchView::Raw(i) => self.node(|v| v.visit_raw(&i), |_| ControlFlow::Continue(())),
+            ToMatchView::String(i) => {
+                self.node(|v| v.visit_string(&i), |_| ControlFlow::Continue(()))
+            }
+            ToMatchView::List(list) => self.node(
+                |v| v.visit_list(&list),
+                |w| {
+                    for (index, i) in list.iter().enumerate() {
+                        w.node(|v| v.visit_list_item(index, &i), |w| w.walk(i.clone()))?;
+                    }
+                    ControlFlow::Continue(())
+                },
+            ),
+            ToMatchView::Map(map) => self.node(|v| v.visit_map(&map), |w| w.entries(&map)),
+            ToMatchView::Tagged(i) => self.node(|v| v.visit_tagged(&i), |w| w.walk(i.view())),
+            ToMatchView::File(i) => self.node(
+                |v| v.visit_file(&i),
+                |w| {
+                    w.entries(&i.anchors().file_anchors())?;
+                    w.walk(i.view())
+                },
+            ),
+            ToMatchView::TakeAnchor(i) => {
+                self.node(|v| v.visit_take_anchor(&i), |w| w.walk(i.view()))
+            }
+            ToMatchView::GetAnchor(i) => self.node(
+                |v| v.visit_get_anchor(&i),
+                |w| {
+                    let target = i.view();
+                    match w.options.follow_anchors {
+                        FollowAnchors::Always if !w.stack.contains(&target.index()) => {
+                            w.walk(target)
+                        }
+                        _ => ControlFlow::Continue(()),
+                    }
+                },
+            ),
+        };
+        self.stack.pop();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cst::document::Cst,
+        data::{
+            data::Data,
+            node::node::{GetAnchorNode, ListNode, MarkedNode, Node, TakeAnchorNode},
+            view::options::{ExpansionLimits, ViewOptions},
+        },
+    };
+
+    const SOURCE: &str = "name: &name main
+list:
+	- = tag: null
+	- *name
+skip:
+	- hidden
+";
+
+    fn data() -> Data {
+        Cst::parse("test.ieml", SOURCE).unwrap().to_data().unwrap()
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+        stop_at: Option<&'static str>,
+    }
+
+    impl Recorder {
+        fn push(&mut self, event: String) -> Walk {
+            let walk = match (event.as_str(), self.stop_at) {
+                ("entry skip", _) => Walk::SkipChildren,
+                (event, Some(stop_at)) if event == stop_at => Walk::Stop,
+                _ => Walk::Continue,
+            };
+            self.events.push(event);
+            walk
+        }
+    }
+
+    impl<'data> Visitor<'data, ()> for Recorder {
+        fn visit_null(&mut self, _: &NullView) -> Walk {
+            self.push("null".into())
+        }
+
+        fn visit_raw(&mut self, raw: &RawView<'data>) -> Walk {
+            self.push(format!("raw {}", raw.raw()))
+        }
+
+        fn visit_list(&mut self, _: &ListView<'data, ()>) -> Walk {
+            self.push("list".into())
+        }
+
+        fn visit_list_item(&mut self, index: usize, _: &View<'data, ()>) -> Walk {
+            self.push(format!("item {}", index))
+        }
+
+        fn visit_map(&mut self, _: &MapView<'data, ()>) -> Walk {
+            self.push("map".into())
+        }
+
+        fn visit_map_entry(&mut self, key: &'data str, _: &View<'data, ()>) -> Walk {
+            self.push(format!("entry {}", key))
+        }
+
+        fn visit_tagged(&mut self, tagged: &TaggedView<'data, ()>) -> Walk {
+            self.push(format!("tagged {}", tagged.tag()))
+        }
+
+        fn visit_take_anchor(&mut self, take_anchor: &TakeAnchorView<'data, ()>) -> Walk {
+            self.push(format!("take {}", take_anchor.name()))
+        }
+
+        fn visit_get_anchor(&mut self, get_anchor: &GetAnchorView<'data, ()>) -> Walk {
+            self.push(format!("get {}", get_anchor.name()))
+        }
+    }
+
+    fn walk(
+        data: &Data,
+        options: WalkOptions,
+        stop_at: Option<&'static str>,
+    ) -> (Vec<String>, ControlFlow<()>) {
+        let mut recorder = Recorder {
+            stop_at,
+            ..Default::default()
+        };
+        let flow = data.view().walk(&mut recorder, options).unwrap();
+        (recorder.events, flow)
+    }
+
+    #[test]
+    fn test_walk() {
+        let data = data();
+        let expected = [
+            "map",
+            "entry name",
+            "take name",
+            "raw main",
+            "entry list",
+            "list",
+            "item 0",
+            "tagged tag",
+            "null",
+            "item 1",
+            "get name",
+            "entry skip",
+        ];
+        let (events, flow) = walk(&data, Default::default(), None);
+        assert_eq!(events, expected);
+        assert_eq!(flow, ControlFlow::Continue(()));
+
+        let options = WalkOptions::new(Order::Post, FollowAnchors::Never);
+        let expected = [
+            "raw main",
+            "take name",
+            "entry name",
+            "null",
+            "tagged tag",
+            "item 0",
+            "get name",
+            "item 1",
+            "list",
+            "entry list",
+            "raw hidden",
+            "item 0",
+            "list",
+            "entry skip",
+            "map",
+        ];
+        assert_eq!(walk(&data, options, None).0, expected);
+
+        let options = WalkOptions::new(Order::Pre, FollowAnchors::Always);
+        let (events, flow) = walk(&data, options, None);
+        assert_eq!(events[10..], ["get name", "raw main", "entry skip"]);
+        assert_eq!(flow, ControlFlow::Continue(()));
+
+        let (events, flow) = walk(&data, Default::default(), Some("tagged tag"));
+        assert_eq!(events.last().map(String::as_str), Some("tagged tag"));
+        assert_eq!(events.len(), 8);
+        assert_eq!(flow, ControlFlow::Break(()));
+    }
+
+    #[test]
+    fn test_walk_cycle() {
+        // Parsing rejects cyclic references, so the nodes are made by hand.
+        let node = |node| MarkedNode::new(node, Default::default());
+        let data = Data::new([
+            node(Node::Raw("a".into())),
+            node(Node::GetAnchor(GetAnchorNode::new("list".into(), 2))),
+            node(Node::List(ListNode::new(vec![0, 1]))),
+            node(Node::TakeAnchor(TakeAnchorNode::new("list".into(), 2))),
+        ]);
+        let options = WalkOptions::new(Order::Pre, FollowAnchors::Always);
+        let mut recorder = Recorder::default();
+        assert!(data.view().walk(&mut recorder, options).is_err());
+
+        let limits = ViewOptions::default().with_limits(ExpansionLimits::unlimited());
+        let view = data.view().with_options(limits);
+        assert_eq!(
+            view.walk(&mut recorder, options),
+            Ok(ControlFlow::Continue(()))
+        );
+        let expected = ["take list", "list", "item 0", "raw a", "item 1", "get list"];
+        assert_eq!(recorder.events, expected);
+    }
+}
@@ -0,0 +1,400 @@
+use super::{options::EmitOptions, writer::Writer};
+use crate::data::{
+    data::Data,
+    view::{analyse_anchors::AnalyseAnchors, to_match::FileView, view::View, ToMatchView},
+};
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+/// Writes the node and everything under it as IEML text.
+pub fn emit_view<'data, A: AnalyseAnchors<'data>>(
+    view: View<'data, A>,
+    options: EmitOptions,
+) -> String {
+    let mut writer = Writer::new(options);
+    writer.root(view);
+    writer.finish()
+}
+
+/// Writes the document as IEML text.
+pub fn emit(data: &Data, options: EmitOptions) -> String {
+    emit_view(data.view(), options)
+}
+
+/// Finds the files included directly by the file the node is written in.
+fn includes<'data, A: AnalyseAnchors<'data>>(
+    view: View<'data, A>,
+    result: &mut Vec<FileView<'data, A>>,
+) {
+    match view.to_match() {
+        ToMatchView::List(i) => i.iter().for_each(|i| includes(i, result)),
+        ToMatchView::Map(i) => i.iter().for_each(|(_, i)| includes(i, result)),
+        ToMatchView::Tagged(i) => includes(i.view(), result),
+        ToMatchView::TakeAnchor(i) => includes(i.view(), result),
+        ToMatchView::File(i) => {
+            let anchors = i.anchors().file_anchors();
+            anchors.iter().for_each(|(_, i)| includes(i, result));
+            result.push(i);
+        }
+        _ => {}
+    }
+}
+
+/// Removes `.` and `name/..` from the path without accessing the file system.
+pub(super) fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for i in path.components() {
+        match i {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(result.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                result.pop();
+            }
+            i => result.push(i),
+        }
+    }
+    result
+}
+
+/// Writes the document as IEML text, each included file separately.
+///
+/// Returns the path and the text of every file, the root file comes first and the rest are
+/// sorted by path. Files refer to the files they include with `< path` relative to their own
+/// directory. A file included several times is written once.
+///
+/// A root that is not a `File` has no path, it is returned with an empty one.
+pub fn emit_files(data: &Data) -> Vec<(PathBuf, String)> {
+    let options = EmitOptions::default();
+    let root = match data.view().to_match() {
+        ToMatchView::File(i) => (i.path(), i.view()),
+        _ => (Path::new(""), data.view()),
+    };
+    let mut result = Vec::new();
+    let mut stack = vec![root];
+    while let Some((path, view)) = stack.pop() {
+        let path = normalize(path);
+        if result.iter().any(|(i, _): &(PathBuf, String)| *i == path) {
+            continue;
+        }
+        let mut files = Vec::new();
+        includes(view.clone(), &mut files);
+        stack.extend(files.into_iter().map(|i| (i.path(), i.view())));
+        result.push((path, emit_view(view, options)));
+    }
+    result[1..].sort_by(|a, b| a.0.cmp(&b.0));
+    result
+}
+
+/// Writes the document to the files it was read from, creating missing directories.
+///
+/// Fails with `io::ErrorKind::InvalidInput` before writing anything if the root is not a `File`
+/// with a path, as there is no place to write it to.
+pub fn write_files(data: &Data) -> io::Result<()> {
+    let files = emit_files(data);
+    if matches!(files.first(), Some((i, _)) if i.as_os_str().is_empty()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The root of the document is not a file with a path, there is no place to write it to.",
+        ));
+    }
+    for (path, text) in files {
+        fs::create_dir_all(path.parent().unwrap_or(Path::new("")))?;
+        fs::write(path, text)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::options::FileStyle;
+    use super::*;
+    use crate::{
+        cst::document::Cst,
+        data::{
+            make::{self, error::marked::MakeResult, Maker},
+            mark::Mark,
+            node::node::{MarkedNode, Node},
+            string_style::StringStyle,
+            value::Value,
+        },
+        test_dir::TestDir,
+    };
+    use std::convert::Infallible;
+
+    type MakeNode<'a> = Box<dyn FnOnce(&mut Maker) -> MakeResult<(), Infallible> + 'a>;
+
+    fn make_data() -> Data {
+        let mark = Mark::default();
+        let servers: Vec<MakeNode> = vec![
+            Box::new(make::tag(
+                mark,
+                "server",
+                make::map(
+                    mark,
+                    (),
+                    vec![
+                        ("name", Box::new(make::string(mark, (), "main")) as MakeNode),
+                        ("port", Box::new(make::raw(mark, (), "8080"))),
+                    ]
+                    .into_iter(),
+                ),
+            )),
+            Box::new(make::null(mark, ())),
+        ];
+        let entries: Vec<(&str, MakeNode)> = vec![
+            (
+                "servers",
+                Box::new(make::list(mark, (), servers.into_iter())),
+            ),
+            (
+                "motd",
+                Box::new(make::take_anchor(
+                    mark,
+                    "motd",
+                    make::string(mark, (), "hello\n\tworld"),
+                )),
+            ),
+            ("copy", Box::new(make::get_anchor(mark, (), "motd"))),
+            (
+                "empty",
+                Box::new(make::list(mark, (), Vec::<MakeNode>::new().into_iter())),
+            ),
+            (
+                "include",
+                Box::new(make::file(
+                    mark,
+                    (),
+                    "include.ieml".into(),
+                    vec![(
+                        "motd",
+                        Box::new(make::get_anchor(mark, (), "motd")) as MakeNode,
+                    )]
+                    .into_iter(),
+                    make::raw(mark, (), "included"),
+                )),
+            ),
+        ];
+        make::make(mark, make::map(mark, (), entries.into_iter())).unwrap()
+    }
+
+    #[test]
+    fn test_emit() {
+        let data = make_data();
+        let expected = "servers:
+	- = server:
+		name: > main
+		port: 8080
+	- null
+motd: &motd >>
+	hello
+		world
+copy: *motd
+empty: []
+include: < include.ieml
+	motd: *motd
+";
+        assert_eq!(emit(&data, Default::default()), expected);
+        assert_eq!(data.to_string(), expected);
+
+        let data = make_data();
+        let options = EmitOptions::new(FileStyle::Inline);
+        let source = emit(&data, options);
+        let parsed = Cst::parse("", &source).unwrap().to_data().unwrap();
+        let file = data
+            .view()
+            .map()
+            .unwrap()
+            .get("include")
+            .unwrap()
+            .file()
+            .unwrap();
+        assert_eq!(parsed.view().map().unwrap().len(), 5);
+        for (key, i) in parsed.view().map().unwrap() {
+            let value = data.view().map().unwrap().get(key).unwrap();
+            match key.as_str() {
+                "include" => assert_eq!(i, file.view()),
+                _ => assert_eq!(i, value),
+            }
+        }
+    }
+
+    #[test]
+    fn test_emit_string_style() {
+        let mark = Mark::default();
+        let classic = StringStyle::Classic { escaped: false };
+        let entries: Vec<(&str, MakeNode)> = vec![
+            (
+                "a",
+                Box::new(make::string_with_style(mark, (), "x\ny", classic)),
+            ),
+            (
+                "b",
+                Box::new(make::string_with_style(mark, (), "\"x\"", classic)),
+            ),
+            (
+                "c",
+                Box::new(make::string_with_style(mark, (), "x\ny", StringStyle::Line)),
+            ),
+        ];
+        let data = make::make(mark, make::map(mark, (), entries.into_iter())).unwrap();
+        let expected = "a: \"x\n\ty\"\nb: > \"x\"\nc: >>\n\tx\n\ty\n";
+        assert_eq!(data.to_string(), expected);
+
+        let parsed = Cst::parse("", expected).unwrap().to_data().unwrap();
+        let map = parsed.view().map().unwrap();
+        assert_eq!(map.get("a").unwrap().string().unwrap().string(), "x\ny");
+        assert_eq!(map.get("a").unwrap().string().unwrap().style(), classic);
+        assert_eq!(map.get("b").unwrap().string().unwrap().string(), "\"x\"");
+    }
+
+    #[test]
+    fn test_emit_keys() {
+        let mark = Mark::default();
+        let keys = ["x: y", "-k", "#k", "a\nb", "", "- k", "\"k\"", "key"];
+        let raws = [
+            "", "null", "a\nb", "- x", "k: v", "# c", "\"q\"", "*a", "> s", "8080",
+        ];
+        let lines = ["", " a", "a ", "# c", "x: y"];
+        let names = (0..raws.len() + lines.len())
+            .map(|i| format!("value{}", i))
+            .collect::<Vec<_>>();
+        let entries = keys
+            .iter()
+            .map(|i| (*i, Box::new(make::string(mark, (), *i)) as MakeNode))
+            .chain(names.iter().zip(raws).map(|(name, raw)| {
+                (
+                    name.as_str(),
+                    Box::new(make::raw(mark, (), raw)) as MakeNode,
+                )
+            }))
+            .chain(names[raws.len()..].iter().zip(lines).map(|(name, line)| {
+                let string = make::string_with_style(mark, (), line, StringStyle::Line);
+                (name.as_str(), Box::new(string) as MakeNode)
+            }))
+            .collect::<Vec<_>>();
+        let data = make::make(mark, make::map(mark, (), entries.into_iter())).unwrap();
+        let source = emit(&data, Default::default());
+        assert!(source.contains("\"x: y\": > x: y\n"));
+        assert!(source.contains("\n\"a\\nb\": >>\n"));
+        assert!(source.contains("key: > key\n"));
+        assert!(source.contains("value9: 8080\n"));
+
+        let parsed = Cst::parse("", &source).unwrap().to_data().unwrap();
+        let decode = |data: &Data| data.view().decode::<Infallible, Value>().unwrap();
+        let Value::Map(mut expected) = decode(&data) else {
+            unreachable!()
+        };
+        // Raw values that would be read back as other nodes are written as strings.
+        for name in &names[..raws.len() - 1] {
+            let (_, value) = expected.iter_mut().find(|(i, _)| i == name).unwrap();
+            let value = &mut value.value;
+            *value = Value::String(value.as_raw().unwrap().into());
+        }
+        assert_eq!(decode(&parsed), Value::Map(expected));
+    }
+
+    #[test]
+    fn test_emit_files() {
+        let dir = TestDir::new("emit_files");
+        let main = "# root
+first: < sub/first.ieml
+\tname: > main
+second: < shared.ieml
+";
+        let first = "shared: < ../shared.ieml\nvalue: *name\n";
+        let shared = "- a\n- b\n";
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("main.ieml"), main).unwrap();
+        std::fs::write(dir.join("sub/first.ieml"), first).unwrap();
+        std::fs::write(dir.join("shared.ieml"), shared).unwrap();
+
+        let data = Cst::parse(dir.join("main.ieml"), main)
+            .unwrap()
+            .to_data()
+            .unwrap();
+        let files = emit_files(&data);
+        let expected = vec![
+            (dir.join("main.ieml"), main.to_string()),
+            (dir.join("shared.ieml"), shared.to_string()),
+            (dir.join("sub/first.ieml"), first.to_string()),
+        ];
+        assert_eq!(files, expected);
+
+        write_files(&data).unwrap();
+        let read = |path: &str| std::fs::read_to_string(dir.join(path)).unwrap();
+        assert_eq!(read("main.ieml"), main);
+        assert_eq!(read("sub/first.ieml"), first);
+        assert_eq!(read("shared.ieml"), shared);
+    }
+
+    #[test]
+    fn test_write_files_without_root_file() {
+        let mark = Mark::default();
+        let data = make::make(mark, make::raw::<_, Infallible, _>(mark, (), "value")).unwrap();
+        let error = write_files(&data).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        let data = Data::new([MarkedNode::new(Node::Raw("value".into()), mark)]);
+        assert_eq!(
+            emit_files(&data),
+            vec![(PathBuf::new(), "value\n".to_string())]
+        );
+        let error = write_files(&data).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_emit_relative_path() {
+        let mark = Mark::default();
+        let include = make::file(
+            mark,
+            (),
+            "b/x.ieml".into(),
+            Vec::<(&str, MakeNode)>::new().into_iter(),
+            make::raw(mark, (), "included"),
+        );
+        let entries = vec![("include", Box::new(include) as MakeNode)];
+        let root = make::file(
+            mark,
+            (),
+            "a/main.ieml".into(),
+            Vec::<(&str, MakeNode)>::new().into_iter(),
+            make::map(mark, (), entries.into_iter()),
+        );
+        let data = make::make(mark, root).unwrap();
+        let main = data.view().file().unwrap().view();
+        let expected = "include: < ../b/x.ieml\n";
+        assert_eq!(emit_view(main, Default::default()), expected);
+    }
+
+    #[test]
+    fn test_emit_inline_file() {
+        let data = make_data();
+        let options = EmitOptions::new(FileStyle::Inline);
+        let result = emit(&data, options);
+        assert!(result.contains("\ninclude: included\n"));
+
+        let view = data.view().map().unwrap().get("servers").unwrap();
+        let expected = "- = server:\n\tname: > main\n\tport: 8080\n- null\n";
+        assert_eq!(emit_view(view, options), expected);
+    }
+
+    #[test]
+    fn test_emit_inline_file_anchors() {
+        let dir = TestDir::new("emit_inline_file_anchors");
+        let main = "include: < inc.ieml\n\tname: > main\n";
+        std::fs::write(dir.join("inc.ieml"), "value: *name\n").unwrap();
+        let data = Cst::parse(dir.join("main.ieml"), main)
+            .unwrap()
+            .to_data()
+            .unwrap();
+        let result = emit(&data, EmitOptions::new(FileStyle::Inline));
+        assert_eq!(result, "include:\n\tvalue: > main\n");
+        let parsed = Cst::parse("", &result).unwrap().to_data().unwrap();
+        let value = parsed.view().at("include.value").unwrap();
+        assert_eq!(value.string().unwrap().string(), "main");
+    }
+}
@@ -0,0 +1,4 @@
+pub mod emitter;
+pub mod options;
+pub(crate) mod string;
+mod writer;
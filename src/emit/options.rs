@@ -0,0 +1,21 @@
+/// How `File` nodes are written.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum FileStyle {
+    /// Writes a `< path` reference followed by the anchors passed to the file.
+    #[default]
+    Reference,
+    /// Writes the contents of the file in place of the reference, references to the anchors passed
+    /// to the file are replaced with their values.
+    Inline,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct EmitOptions {
+    pub file_style: FileStyle,
+}
+
+impl EmitOptions {
+    pub fn new(file_style: FileStyle) -> Self {
+        Self { file_style }
+    }
+}
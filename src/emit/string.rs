@@ -0,0 +1,62 @@
+use crate::data::string_style::StringStyle;
+
+/// Gets the style to write the string in, the given one if it fits the string.
+pub(crate) fn fitting_style(string: &str, style: StringStyle) -> StringStyle {
+    match style.fits(string) {
+        true => style,
+        false if string.contains('\n') => StringStyle::NotEscaped,
+        false => StringStyle::Line,
+    }
+}
+
+/// Writes the string in the style without the space before it.
+///
+/// The comment is written after `>>` or after the closing quote, a line string has no place
+/// for it. The lines of the string after the first start with `indent`.
+pub(crate) fn write_string(
+    output: &mut String,
+    string: &str,
+    style: StringStyle,
+    indent: usize,
+    comment: Option<&str>,
+) {
+    let push_comment = |output: &mut String| {
+        if let Some(comment) = comment {
+            output.push(' ');
+            output.push_str(comment);
+        }
+    };
+    let push_line = |output: &mut String| {
+        output.push('\n');
+        output.push_str(&"\t".repeat(indent));
+    };
+    match style {
+        StringStyle::Line => {
+            output.push_str("> ");
+            output.push_str(string);
+        }
+        StringStyle::NotEscaped => {
+            output.push_str(">>");
+            push_comment(output);
+            for line in string.split('\n') {
+                push_line(output);
+                output.push_str(line);
+            }
+        }
+        StringStyle::Classic { escaped } => {
+            output.push('"');
+            for i in string.chars() {
+                match i {
+                    '\\' => output.push_str("\\\\"),
+                    '"' => output.push_str("\\\""),
+                    '\n' if escaped => output.push_str("\\n"),
+                    '\n' => push_line(output),
+                    '\t' if escaped => output.push_str("\\t"),
+                    i => output.push(i),
+                }
+            }
+            output.push('"');
+            push_comment(output);
+        }
+    }
+}
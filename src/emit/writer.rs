@@ -0,0 +1,253 @@
+use super::{
+    emitter::normalize,
+    options::{EmitOptions, FileStyle},
+    string::{fitting_style, write_string},
+};
+use crate::{
+    cst::parser::{is_raw, key_text},
+    data::{
+        node_id::NodeId,
+        string_style::StringStyle,
+        view::{
+            analyse_anchors::AnalyseAnchors,
+            to_match::{FileView, ListView, MapView},
+            view::View,
+            ToMatchView,
+        },
+    },
+};
+use std::path::{Component, Path, PathBuf};
+
+/// Gets the path relative to the directory by comparing their components, `../` is added for
+/// each component of the directory the path does not share.
+///
+/// The path is returned as it is if only one of them is absolute.
+fn relative(path: &Path, dir: &Path) -> PathBuf {
+    let (path, dir) = (normalize(path), normalize(dir));
+    if path.has_root() != dir.has_root() {
+        return path;
+    }
+    let common = path
+        .components()
+        .zip(dir.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let parents = dir.components().skip(common).map(|_| Component::ParentDir);
+    parents
+        .chain(path.components().skip(common))
+        .map(|i| i.as_os_str())
+        .collect()
+}
+
+pub(super) struct Writer {
+    options: EmitOptions,
+    output: String,
+    /// The comment to be written at the end of the current line.
+    comment: Option<String>,
+    /// Whether a comment can be written at the end of the current line.
+    line_open: bool,
+    /// Directory of the file being written, paths of the files it includes are relative to it.
+    dir: PathBuf,
+    /// Handles of the anchors passed to the files written inline. The anchors are not written, so
+    /// a reference to one of them is replaced with its value.
+    inlined: Vec<NodeId>,
+}
+
+impl Writer {
+    pub(super) fn new(options: EmitOptions) -> Self {
+        Self {
+            options,
+            output: String::new(),
+            comment: None,
+            line_open: false,
+            dir: PathBuf::new(),
+            inlined: Vec::new(),
+        }
+    }
+
+    pub(super) fn finish(self) -> String {
+        self.output
+    }
+
+    /// Writes the node as a whole document, a `File` node at the root is the document itself.
+    pub(super) fn root<'data, A: AnalyseAnchors<'data>>(&mut self, view: View<'data, A>) {
+        let view = match view.to_match() {
+            ToMatchView::File(i) => i.view(),
+            _ => view,
+        };
+        self.dir = view.file_path().parent().unwrap_or(Path::new("")).into();
+        self.leading(&view, 0);
+        let block = match view.to_match() {
+            ToMatchView::List(i) => i.len() != 0,
+            ToMatchView::Map(i) => i.len() != 0,
+            _ => false,
+        };
+        match (block, view.comments()) {
+            (true, Some(comments)) => {
+                self.node(view, 0);
+                for i in comments.trailing.iter() {
+                    self.push_line(0);
+                    self.output.push_str(i);
+                }
+            }
+            _ => self.entry(view, 0),
+        }
+        let begin = self.output.len() - self.output.trim_start_matches([' ', '\n']).len();
+        self.output.drain(..begin);
+        self.output.push('\n');
+    }
+
+    fn new_line(&mut self, indent: usize) {
+        if let Some(comment) = self.comment.take() {
+            self.output.push(' ');
+            self.output.push_str(&comment);
+        }
+        self.push_line(indent);
+    }
+
+    /// Starts a new line without ending the current one with a comment.
+    fn push_line(&mut self, indent: usize) {
+        self.output.push('\n');
+        self.output.push_str(&"\t".repeat(indent));
+    }
+
+    /// Writes the leading comments of the node, each on its own line.
+    fn leading<'data, A: AnalyseAnchors<'data>>(&mut self, view: &View<'data, A>, indent: usize) {
+        for i in view.comments().iter().flat_map(|i| i.leading.iter()) {
+            self.new_line(indent);
+            self.output.push_str(i);
+        }
+    }
+
+    /// Writes the list item or map entry value together with its trailing comments.
+    ///
+    /// The first trailing comment goes at the end of the first line of the node if the node allows
+    /// it, the rest go on their own lines after the node.
+    fn entry<'data, A: AnalyseAnchors<'data>>(&mut self, view: View<'data, A>, indent: usize) {
+        let Some(comments) = view.comments() else {
+            return self.node(view, indent);
+        };
+        let mut trailing = comments.trailing.iter();
+        self.comment = trailing.next().cloned();
+        self.node(view, indent);
+        if let Some(comment) = self.comment.take() {
+            match self.line_open {
+                true => self.output.push(' '),
+                false => self.push_line(indent),
+            }
+            self.output.push_str(&comment);
+        }
+        for i in trailing {
+            self.push_line(indent);
+            self.output.push_str(i);
+        }
+    }
+
+    /// Writes the node after a prefix such as `key:` or `-`.
+    ///
+    /// Scalars continue the current line, lists and maps start on the next line with `indent`.
+    fn node<'data, A: AnalyseAnchors<'data>>(&mut self, view: View<'data, A>, indent: usize) {
+        self.line_open = false;
+        match view.to_match() {
+            ToMatchView::Null(_) => {
+                self.output.push_str(" null");
+                self.line_open = true;
+            }
+            ToMatchView::Raw(i) if is_raw(i.raw()) => {
+                self.output.push(' ');
+                self.output.push_str(i.raw());
+            }
+            // A raw value that would be read back as another node is written as a string.
+            ToMatchView::Raw(i) => {
+                self.string(i.raw(), StringStyle::Classic { escaped: true }, indent)
+            }
+            ToMatchView::String(i) => self.string(i.string(), i.style(), indent),
+            ToMatchView::List(i) => self.list(i, indent),
+            ToMatchView::Map(i) => self.map(i, indent),
+            ToMatchView::Tagged(i) => {
+                self.output.push_str(" = ");
+                self.output.push_str(i.tag());
+                self.output.push(':');
+                self.node(i.view(), indent);
+            }
+            ToMatchView::File(i) => self.file(i, indent),
+            ToMatchView::TakeAnchor(i) => {
+                self.output.push_str(" &");
+                self.output.push_str(i.name());
+                self.node(i.view(), indent);
+            }
+            ToMatchView::GetAnchor(i) if self.inlined.contains(&i.view().id()) => {
+                self.node(i.view(), indent)
+            }
+            ToMatchView::GetAnchor(i) => {
+                self.output.push_str(" *");
+                self.output.push_str(i.name());
+                self.line_open = true;
+            }
+        }
+    }
+
+    /// Writes the string in its style, or in the closest one that fits it.
+    fn string(&mut self, string: &str, style: StringStyle, indent: usize) {
+        let style = fitting_style(string, style);
+        let comment = match style {
+            StringStyle::NotEscaped => self.comment.take(),
+            _ => None,
+        };
+        self.output.push(' ');
+        write_string(&mut self.output, string, style, indent, comment.as_deref());
+        self.line_open = matches!(style, StringStyle::Classic { .. });
+    }
+
+    fn list<'data, A: AnalyseAnchors<'data>>(&mut self, list: ListView<'data, A>, indent: usize) {
+        if list.len() == 0 {
+            self.output.push_str(" []");
+            self.line_open = true;
+            return;
+        }
+        for i in list.iter() {
+            self.leading(&i, indent);
+            self.new_line(indent);
+            self.output.push('-');
+            self.entry(i, indent + 1);
+        }
+    }
+
+    fn map<'data, A: AnalyseAnchors<'data>>(&mut self, map: MapView<'data, A>, indent: usize) {
+        if map.len() == 0 {
+            self.output.push_str(" {}");
+            self.line_open = true;
+            return;
+        }
+        for (key, i) in map.iter() {
+            self.leading(&i, indent);
+            self.new_line(indent);
+            self.output.push_str(&key_text(key));
+            self.output.push(':');
+            self.entry(i, indent + 1);
+        }
+    }
+
+    fn file<'data, A: AnalyseAnchors<'data>>(&mut self, file: FileView<'data, A>, indent: usize) {
+        match self.options.file_style {
+            FileStyle::Reference => {
+                let path = relative(file.path(), &self.dir);
+                self.output.push_str(" < ");
+                self.output.push_str(&path.to_string_lossy());
+                self.line_open = true;
+                let file_anchors = file.anchors().file_anchors();
+                if file_anchors.len() != 0 {
+                    self.map(file_anchors, indent);
+                }
+            }
+            FileStyle::Inline => {
+                let len = self.inlined.len();
+                let file_anchors = file.anchors().file_anchors();
+                let ids = file_anchors.iter().map(|(_, i)| i.id());
+                self.inlined.extend(ids);
+                self.node(file.view(), indent);
+                self.inlined.truncate(len);
+            }
+        }
+    }
+}
@@ -1,2 +1,6 @@
+pub mod cst;
 pub mod data;
+pub mod emit;
 pub mod parse;
+#[cfg(test)]
+mod test_dir;
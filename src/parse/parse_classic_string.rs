@@ -7,7 +7,7 @@ use super::{
     },
     utils::combinator::{match_indent, skip_blank_line},
 };
-use crate::data::{make, mark::Mark};
+use crate::data::{make, mark::Mark, string_style::StringStyle};
 use nom::character::complete::*;
 
 fn analyze<'input, 'path: 'input>(
@@ -88,6 +88,23 @@ fn parse(input: &str, indent: usize, capacity: usize) -> String {
     result
 }
 
+/// Whether the classic string, starting with its opening quote, uses escape sequences.
+pub(crate) fn is_escaped(input: &str) -> bool {
+    let mut iter = input.chars().skip(1);
+    while let Some(i) = iter.next() {
+        match i {
+            '\"' => return false,
+            '\\' => {
+                if let Some('\\' | '\"' | 't' | 'n' | '\n') = iter.next() {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
 pub(crate) fn classic_string<'input, 'path: 'input>(
     file_path: &'path Path,
     input: &'input str,
@@ -113,7 +130,10 @@ pub(crate) fn parse_classic_string<'input, 'path: 'input>(
     mark: Mark,
 ) -> impl FnOnce(&mut make::Maker) -> MakeResult<'input> {
     move |maker| {
-        let map = |(output, string)| make::string(mark, output, string)(maker);
+        let style = StringStyle::Classic {
+            escaped: is_escaped(input),
+        };
+        let map = |(output, string)| make::string_with_style(mark, output, string, style)(maker);
         classic_string(file_path, input, indent, mark).and_then(map)
     }
 }
@@ -124,6 +144,15 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_is_escaped() {
+        assert!(!is_escaped(r#""hello""#));
+        assert!(!is_escaped(r#""hello \world""#));
+        assert!(is_escaped(r#""hello\n""#));
+        assert!(is_escaped(r#""hello \"world\"""#));
+        assert!(!is_escaped(r#""hello" \n"#));
+    }
+
     #[test]
     fn test_classic_string() {
         let begin_mark = Mark::new(0, 0);
@@ -7,7 +7,7 @@ use super::{
     },
     utils::combinator::match_line,
 };
-use crate::data::{make, mark::Mark};
+use crate::data::{make, mark::Mark, string_style::StringStyle};
 use nom::bytes::complete::tag;
 
 pub(crate) fn line_string<'input, 'path: 'input>(
@@ -30,7 +30,8 @@ pub(crate) fn parse_line_string<'input, 'path: 'input>(
     mark: Mark,
 ) -> impl FnOnce(&mut make::Maker) -> MakeResult<'input> {
     move |maker| {
-        let map = |(output, string)| make::string(mark, output, string)(maker);
+        let style = StringStyle::Line;
+        let map = |(output, string)| make::string_with_style(mark, output, string, style)(maker);
         line_string(file_path, input, mark).and_then(map)
     }
 }
@@ -9,7 +9,7 @@ use super::{
         match_enter, match_indent, match_line, skip_blank_line, skip_enter, skip_indent,
     },
 };
-use crate::data::{make, mark::Mark};
+use crate::data::{make, mark::Mark, string_style::StringStyle};
 use nom::bytes::complete::tag;
 
 fn analyze<'input, 'path: 'input>(
@@ -78,7 +78,8 @@ pub(crate) fn parse_not_escaped_string<'input, 'path: 'input>(
     mark: Mark,
 ) -> impl FnOnce(&mut make::Maker) -> MakeResult<'input> {
     move |maker| {
-        let map = |(output, string)| make::string(mark, output, string)(maker);
+        let style = StringStyle::NotEscaped;
+        let map = |(output, string)| make::string_with_style(mark, output, string, style)(maker);
         not_escaped_string(file_path, input, indent, mark).and_then(map)
     }
 }
@@ -0,0 +1,33 @@
+use std::{
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    process,
+};
+
+/// Directory for the files of a test, removed together with its contents when dropped.
+pub(crate) struct TestDir(PathBuf);
+
+impl TestDir {
+    /// Creates an empty directory unique to the test and to the process running it.
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("ieml_test_{}_{}", process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}